@@ -35,9 +35,14 @@ fn run() -> Result<(), JsValue> {
     let document = window.document().expect("should have a document on window");
     let body = document.body().expect("document should have a body");
 
-    let p = Rc::new(MyView::new());
-    let _selfc = Rc::clone(&p);
-    p.on_init(Rc::new(document), &body, None)?;
+    // The root view lives for as long as the page, so its scope is never released.
+    let scope = tsz::Scope::new();
+
+    let p = scope.enter(|| Rc::new(MyView::new()));
+    scope.own_view(p.clone());
+
+    p.on_init(&scope, Rc::new(document), &body, None)?;
+    std::mem::forget(scope);
 
     Ok(())
 }
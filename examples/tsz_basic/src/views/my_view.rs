@@ -1,6 +1,7 @@
 use super::sub_view::*;
 use tsz::views::*;
 
+#[derive(tsz::Snapshot)]
 pub struct MyView {
     value: State<bool>,
 }
@@ -19,7 +20,7 @@ tsz::view! {
         "The count is {$value}"
 
         div (class: [center]) {
-            button (click: { $value = true }) {
+            button (click: { $value = !$value }) {
                 "Count"
             }
         }
@@ -1,11 +1,11 @@
-use tsz::Binding;
+use tsz::ReadBinding;
 
 pub struct SubView {
-    sub_value: Binding<u64>,
+    sub_value: ReadBinding<u64>,
 }
 
 impl SubView {
-    pub fn new(value_param: Binding<u64>) -> Self {
+    pub fn new(value_param: ReadBinding<u64>) -> Self {
         Self {
             sub_value: value_param,
         }
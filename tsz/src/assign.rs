@@ -0,0 +1,67 @@
+//! Picks the assignment `view!` emits for `$state = value` and `$state += value`.
+//!
+//! Method lookup prefers [`AssignChanged`], implemented on the assignment itself, and falls
+//! back to [`AssignAny`], implemented on a reference to it, only when the value can't be
+//! compared. So equal values are skipped whenever they can be, and any other state can still
+//! be assigned.
+
+use std::cell::Cell;
+
+use crate::StateRefMut;
+
+pub struct Assignment<'a, T>(Cell<Option<(StateRefMut<'a, T>, T)>>);
+
+impl<'a, T> Assignment<'a, T> {
+    pub fn new(state: StateRefMut<'a, T>, value: T) -> Self {
+        Assignment(Cell::new(Some((state, value))))
+    }
+}
+
+pub trait AssignChanged {
+    fn __assign(&self);
+}
+
+impl<T: PartialEq + 'static> AssignChanged for Assignment<'_, T> {
+    fn __assign(&self) {
+        if let Some((state, value)) = self.0.take() {
+            state.assign(value);
+        }
+    }
+}
+
+pub trait AssignAny {
+    fn __assign(&self);
+}
+
+impl<T: 'static> AssignAny for &Assignment<'_, T> {
+    fn __assign(&self) {
+        if let Some((state, value)) = self.0.take() {
+            state.set(value);
+        }
+    }
+}
+
+/// A compound assignment such as `$count += 1`, applied by `f`.
+pub struct Operation<'a, T, F>(Cell<Option<(StateRefMut<'a, T>, F)>>);
+
+impl<'a, T, F: FnOnce(&mut T)> Operation<'a, T, F> {
+    pub fn new(state: StateRefMut<'a, T>, f: F) -> Self {
+        Operation(Cell::new(Some((state, f))))
+    }
+}
+
+impl<T: PartialEq + Clone + 'static, F: FnOnce(&mut T)> AssignChanged for Operation<'_, T, F> {
+    fn __assign(&self) {
+        if let Some((state, f)) = self.0.take() {
+            state.update_if_changed(f);
+        }
+    }
+}
+
+impl<T: 'static, F: FnOnce(&mut T) + 'static> AssignAny for &Operation<'_, T, F> {
+    fn __assign(&self) {
+        if let Some((state, f)) = self.0.take() {
+            state.update(f);
+        }
+    }
+}
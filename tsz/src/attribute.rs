@@ -0,0 +1,97 @@
+use std::borrow::Cow;
+
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Element, HtmlElement};
+
+/// A value that can be assigned to an HTML attribute.
+///
+/// `None` and `false` remove the attribute, and `true` sets it without a value, the way
+/// boolean attributes such as `disabled` work.
+pub trait AttributeValue {
+    /// The text of the attribute, or `None` to remove it.
+    fn into_attribute(self) -> Option<String>;
+}
+
+impl AttributeValue for String {
+    fn into_attribute(self) -> Option<String> {
+        Some(self)
+    }
+}
+
+impl AttributeValue for &str {
+    fn into_attribute(self) -> Option<String> {
+        Some(self.to_string())
+    }
+}
+
+impl AttributeValue for &String {
+    fn into_attribute(self) -> Option<String> {
+        Some(self.clone())
+    }
+}
+
+impl AttributeValue for Cow<'_, str> {
+    fn into_attribute(self) -> Option<String> {
+        Some(self.into_owned())
+    }
+}
+
+impl AttributeValue for bool {
+    fn into_attribute(self) -> Option<String> {
+        self.then(String::new)
+    }
+}
+
+impl<T: AttributeValue> AttributeValue for Option<T> {
+    fn into_attribute(self) -> Option<String> {
+        self.and_then(T::into_attribute)
+    }
+}
+
+macro_rules! impl_attribute_value {
+    ($($ty:ty),*) => {
+        $(
+            impl AttributeValue for $ty {
+                fn into_attribute(self) -> Option<String> {
+                    Some(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_attribute_value!(
+    char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64
+);
+
+/// Sets the attribute `name` of `element` to `value`, or removes it if the value is `None`.
+pub fn set_attribute(
+    element: &Element,
+    name: &str,
+    value: impl AttributeValue,
+) -> Result<(), JsValue> {
+    match value.into_attribute() {
+        Some(value) => element.set_attribute(name, &value),
+        None => element.remove_attribute(name),
+    }
+}
+
+/// Adds the class `name` to `element` if `on` is true and removes it otherwise, leaving its
+/// other classes alone.
+pub fn toggle_class(element: &Element, name: &str, on: bool) -> Result<(), JsValue> {
+    element.class_list().toggle_with_force(name, on).map(drop)
+}
+
+/// Sets the inline style property `name` of `element` to `value`, or removes it if the value
+/// is `None`, leaving its other properties alone.
+pub fn set_style(element: &Element, name: &str, value: impl AttributeValue) -> Result<(), JsValue> {
+    let style = element
+        .dyn_ref::<HtmlElement>()
+        .ok_or_else(|| JsValue::from_str("Only HTML elements have inline styles"))?
+        .style();
+
+    match value.into_attribute() {
+        Some(value) => style.set_property(name, &value),
+        None => style.remove_property(name).map(drop),
+    }
+}
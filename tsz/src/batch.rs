@@ -0,0 +1,140 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    rc::Weak,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// How many times a single state or observer may run while one batch is flushed before it
+/// is reported as an update loop.
+const MAX_RUNS: usize = 100;
+
+/// Deferred work queued while a batch is open: a state publishing its value, or an
+/// observer re-evaluating after one of its dependencies changed.
+pub(crate) trait Job {
+    fn run(&self);
+}
+
+/// Identifies a job in the queue. Unlike the job's address, an id is never given to another
+/// job once the first one is dropped.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct JobId(u64);
+
+impl JobId {
+    pub(crate) fn new() -> JobId {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        JobId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+#[derive(Default)]
+struct Batch {
+    depth: usize,
+    pending: VecDeque<(JobId, Weak<dyn Job>)>,
+    runs: HashMap<JobId, usize>,
+}
+
+thread_local! {
+    static BATCH: RefCell<Batch> = RefCell::new(Batch::default());
+}
+
+/// Runs `f`, deferring every publish until the outermost batch ends.
+///
+/// Each state or observer that changed during the batch is notified once, with its final
+/// value, in the order it was first changed. Work triggered while the queue is flushed is
+/// queued and de-duplicated the same way.
+pub fn batch<R>(f: impl FnOnce() -> R) -> R {
+    BATCH.with(|batch| batch.borrow_mut().depth += 1);
+    let _open = Open;
+
+    let result = f();
+
+    let outermost = BATCH.with(|batch| batch.borrow().depth == 1);
+    if outermost {
+        flush();
+    }
+
+    result
+}
+
+/// Closes a batch when dropped, including when `f` or one of the queued jobs panicked.
+///
+/// Work queued by a batch that panicked is dropped, so publishes on the thread aren't
+/// deferred forever.
+struct Open;
+
+impl Drop for Open {
+    fn drop(&mut self) {
+        let _ = BATCH.try_with(|batch| {
+            let mut batch = batch.borrow_mut();
+            batch.depth -= 1;
+
+            if batch.depth == 0 {
+                batch.pending.clear();
+                batch.runs.clear();
+            }
+        });
+    }
+}
+
+/// Queues `job` to run when the current batch ends, unless it is already queued.
+///
+/// Outside of a batch the job runs immediately, inside a batch of its own.
+pub(crate) fn schedule(id: JobId, job: Weak<dyn Job>) {
+    let batching = BATCH.with(|batch| {
+        let mut batch = batch.borrow_mut();
+        if batch.depth == 0 {
+            return false;
+        }
+
+        if !batch.pending.iter().any(|(pending, _)| *pending == id) {
+            batch.pending.push_back((id, job.clone()));
+        }
+
+        true
+    });
+
+    if !batching {
+        if let Some(job) = job.upgrade() {
+            run(id, || job.run());
+        }
+    }
+}
+
+/// Runs `f` right away inside a batch, counting it as a run of job `id`.
+pub(crate) fn run(id: JobId, f: impl FnOnce()) {
+    batch(|| {
+        count_run(id);
+        f()
+    })
+}
+
+fn count_run(id: JobId) {
+    let runs = BATCH.with(|batch| {
+        let mut batch = batch.borrow_mut();
+        let runs = batch.runs.entry(id).or_default();
+        *runs += 1;
+        *runs
+    });
+
+    if runs > MAX_RUNS {
+        panic!(
+            "Update loop detected: a state or observer ran more than {MAX_RUNS} times in one batch. \
+             Check for subscribers, memos or effects that write to the states they depend on."
+        );
+    }
+}
+
+fn flush() {
+    loop {
+        let next = BATCH.with(|batch| batch.borrow_mut().pending.pop_front());
+        let Some((id, job)) = next else {
+            break;
+        };
+
+        if let Some(job) = job.upgrade() {
+            count_run(id);
+            job.run();
+        }
+    }
+}
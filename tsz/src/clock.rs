@@ -0,0 +1,227 @@
+//! Time and timers for the reactive combinators.
+//!
+//! In the browser the [`clock`] is a [`BrowserClock`]. On other targets it is a
+//! [`SystemClock`], whose timeouts only run when [`task::run_until_stalled`] is called after
+//! they are due, and tests can switch to a [`FakeClock`] that only moves when advanced with
+//! [`set_clock`].
+//!
+//! [`task::run_until_stalled`]: crate::task::run_until_stalled
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+    time::Duration,
+};
+
+/// A source of time that can run callbacks later.
+pub trait Clock {
+    /// The time elapsed since an arbitrary point.
+    fn now(&self) -> Duration;
+
+    /// Calls `f` once `delay` has passed, unless the returned timer is dropped first.
+    fn set_timeout(&self, delay: Duration, f: Box<dyn FnOnce()>) -> Timer;
+}
+
+/// A callback scheduled with [`Clock::set_timeout`].
+///
+/// Dropping the timer cancels the callback if it hasn't run yet.
+#[must_use = "dropping a `Timer` immediately cancels it"]
+pub struct Timer {
+    cancel: Option<Box<dyn FnOnce()>>,
+}
+
+impl Timer {
+    pub fn new(cancel: impl FnOnce() + 'static) -> Timer {
+        Timer {
+            cancel: Some(Box::new(cancel)),
+        }
+    }
+
+    /// Detaches the handle, letting the callback run even though the timer is dropped.
+    pub fn forget(mut self) {
+        self.cancel = None;
+    }
+
+    pub fn cancel(self) {
+        drop(self)
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            cancel();
+        }
+    }
+}
+
+/// The browser's clock and `setTimeout`.
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BrowserClock;
+
+#[cfg(target_arch = "wasm32")]
+impl Clock for BrowserClock {
+    fn now(&self) -> Duration {
+        Duration::from_secs_f64(js_sys::Date::now() / 1000.0)
+    }
+
+    fn set_timeout(&self, delay: Duration, f: Box<dyn FnOnce()>) -> Timer {
+        use wasm_bindgen::{closure::Closure, JsCast};
+
+        let window = web_sys::window().expect("no global `window` exists");
+        let callback = Closure::once_into_js(f);
+        let handle = window
+            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                callback.unchecked_ref(),
+                delay.as_millis().try_into().unwrap_or(i32::MAX),
+            )
+            .expect("Unable to set timeout");
+
+        Timer::new(move || window.clear_timeout_with_handle(handle))
+    }
+}
+
+/// The system's monotonic clock, with timeouts that run on the local
+/// [`task`](crate::task) executor.
+///
+/// Nothing else drives time natively: a timeout runs from the first
+/// [`run_until_stalled`](crate::task::run_until_stalled) after it is due.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    start: std::time::Instant,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock {
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock::new()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn set_timeout(&self, delay: Duration, f: Box<dyn FnOnce()>) -> Timer {
+        let key = crate::task::set_timeout(std::time::Instant::now() + delay, f);
+
+        Timer::new(move || crate::task::clear_timeout(key))
+    }
+}
+
+/// A pending timer: its deadline, its id and its callback.
+type FakeTimer = (Duration, u64, Box<dyn FnOnce()>);
+
+#[derive(Default)]
+struct FakeClockInner {
+    now: Cell<Duration>,
+    next_id: Cell<u64>,
+    timers: RefCell<Vec<FakeTimer>>,
+}
+
+/// A clock that only moves when [`FakeClock::advance`] is called.
+///
+/// Clones share the same time and timers.
+#[derive(Default, Clone)]
+pub struct FakeClock(Rc<FakeClockInner>);
+
+impl FakeClock {
+    pub fn new() -> FakeClock {
+        FakeClock::default()
+    }
+
+    /// Moves time forward by `by`, running every timer that comes due in order, including
+    /// the ones they schedule.
+    pub fn advance(&self, by: Duration) {
+        let target = self.0.now.get() + by;
+
+        loop {
+            let due = {
+                let mut timers = self.0.timers.borrow_mut();
+                let next = timers
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (deadline, _, _))| *deadline <= target)
+                    .min_by_key(|(_, (deadline, id, _))| (*deadline, *id))
+                    .map(|(index, _)| index);
+
+                next.map(|index| timers.remove(index))
+            };
+
+            let Some((deadline, _, f)) = due else {
+                break;
+            };
+
+            self.0.now.set(deadline);
+            f();
+        }
+
+        self.0.now.set(target);
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Duration {
+        self.0.now.get()
+    }
+
+    fn set_timeout(&self, delay: Duration, f: Box<dyn FnOnce()>) -> Timer {
+        let id = self.0.next_id.get();
+        self.0.next_id.set(id + 1);
+
+        let deadline = self.0.now.get() + delay;
+        self.0.timers.borrow_mut().push((deadline, id, f));
+
+        let clock: Weak<FakeClockInner> = Rc::downgrade(&self.0);
+        Timer::new(move || {
+            if let Some(clock) = clock.upgrade() {
+                // The callback is dropped after the timers are released.
+                let removed = {
+                    let mut timers = clock.timers.borrow_mut();
+                    let index = timers.iter().position(|(_, timer, _)| *timer == id);
+                    index.map(|index| timers.remove(index))
+                };
+                drop(removed);
+            }
+        })
+    }
+}
+
+thread_local! {
+    static CLOCK: RefCell<Option<Rc<dyn Clock>>> = const { RefCell::new(None) };
+}
+
+/// Returns the clock used by the timing combinators.
+pub fn clock() -> Rc<dyn Clock> {
+    CLOCK.with(|clock| {
+        clock
+            .borrow_mut()
+            .get_or_insert_with(|| {
+                #[cfg(target_arch = "wasm32")]
+                let clock = Rc::new(BrowserClock);
+                #[cfg(not(target_arch = "wasm32"))]
+                let clock = Rc::new(SystemClock::new());
+
+                clock
+            })
+            .clone()
+    })
+}
+
+/// Replaces the clock used by the timing combinators created from now on.
+pub fn set_clock(clock: impl Clock + 'static) {
+    CLOCK.with(|current| current.replace(Some(Rc::new(clock))));
+}
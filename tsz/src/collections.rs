@@ -0,0 +1,377 @@
+use std::{
+    cell::{Ref, RefCell},
+    hash::Hash,
+    rc::{Rc, Weak},
+};
+
+use linked_hash_map::LinkedHashMap;
+
+use crate::{
+    batch::{self, Job, JobId},
+    subscription::Subscribers,
+    ReadBinding, State, Subscription,
+};
+
+/// A change made to a [`StateVec`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum VecDiff<T> {
+    /// `value` was inserted at `index`, shifting the following items up.
+    Insert { index: usize, value: T },
+    /// The item at `index` was removed, shifting the following items down.
+    Remove { index: usize },
+    /// The item at `from` was removed and inserted again at `to`.
+    Move { from: usize, to: usize },
+    /// The item at `index` was replaced by `value`.
+    Update { index: usize, value: T },
+    /// Every item was removed.
+    Clear,
+}
+
+/// A change made to a [`StateMap`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapDiff<K, V> {
+    /// `key` was added at the end of the map.
+    Insert { key: K, value: V },
+    /// The value of `key` was replaced by `value`, keeping its position.
+    Update { key: K, value: V },
+    /// `key` was removed.
+    Remove { key: K },
+    /// Every entry was removed.
+    Clear,
+}
+
+/// The diffs recorded by a collection that haven't been published yet.
+struct Changes<D> {
+    id: JobId,
+    this: Weak<Changes<D>>,
+    subscribers: Rc<Subscribers<D>>,
+    pending: RefCell<Vec<D>>,
+}
+
+impl<D: 'static> Changes<D> {
+    fn new() -> Rc<Changes<D>> {
+        Rc::new_cyclic(|this| Changes {
+            id: JobId::new(),
+            this: this.clone(),
+            subscribers: Rc::new(Subscribers::new()),
+            pending: RefCell::new(Vec::new()),
+        })
+    }
+
+    fn subscribe(&self, f: impl FnMut(&D) + 'static) -> Subscription {
+        let key = self.subscribers.insert(Box::new(f));
+        let subscribers = Rc::downgrade(&self.subscribers);
+
+        Subscription::new(subscribers, key)
+    }
+
+    /// Queues the diff made by `diff`, which is only called if someone is listening.
+    fn emit(&self, diff: impl FnOnce() -> D) {
+        if self.subscribers.is_empty() {
+            return;
+        }
+
+        self.pending.borrow_mut().push(diff());
+
+        batch::schedule(self.id, self.this.clone());
+    }
+}
+
+impl<D: 'static> Job for Changes<D> {
+    fn run(&self) {
+        // Every diff is delivered, in order, even if several were made in the same batch.
+        for diff in self.pending.take() {
+            self.subscribers.notify(&diff);
+        }
+    }
+}
+
+/// A list that tells its subscribers what changed instead of only republishing itself.
+///
+/// The whole list can be read and tracked like a [`State<Vec<T>>`], while
+/// [`StateVec::subscribe_diff`] receives a [`VecDiff`] for every change, so a view can
+/// patch the matching nodes.
+pub struct StateVec<T> {
+    items: State<Vec<T>>,
+    changes: Rc<Changes<VecDiff<T>>>,
+}
+
+impl<T: Clone + 'static> StateVec<T> {
+    pub fn new() -> StateVec<T> {
+        Vec::new().into()
+    }
+
+    /// Registers `f` to be called with every change made to the list.
+    pub fn subscribe_diff(&self, f: impl FnMut(&VecDiff<T>) + 'static) -> Subscription {
+        self.changes.subscribe(f)
+    }
+
+    /// Registers `f` to be called with the whole list every time it changes.
+    pub fn subscribe(&self, f: impl FnMut(&Vec<T>) + 'static) -> Subscription {
+        self.items.subscribe(f)
+    }
+
+    /// Immutably borrows the list.
+    pub fn borrow(&self) -> Ref<'_, Vec<T>> {
+        self.items.borrow()
+    }
+
+    pub fn len(&self) -> usize {
+        self.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.borrow().is_empty()
+    }
+
+    /// Returns a clone of the item at `index`.
+    pub fn get(&self, index: usize) -> Option<T> {
+        self.borrow().get(index).cloned()
+    }
+
+    /// Creates a read-only binding to the whole list.
+    pub fn bind(&self) -> ReadBinding<Vec<T>> {
+        self.items.bind()
+    }
+
+    pub fn push(&self, value: T) {
+        let index = self.items.latest().len();
+        self.insert(index, value);
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let len = self.items.latest().len();
+        len.checked_sub(1).map(|index| self.remove(index))
+    }
+
+    /// Inserts `value` at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the list.
+    pub fn insert(&self, index: usize, value: T) {
+        // Batched, so the diff is queued before subscribers of the whole collection run.
+        batch::batch(|| {
+            self.items.update_latest(|items| items.insert(index, value));
+            self.changes.emit(|| VecDiff::Insert {
+                index,
+                value: self.items.latest()[index].clone(),
+            });
+        })
+    }
+
+    /// Removes and returns the item at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&self, index: usize) -> T {
+        batch::batch(|| {
+            let value = self.items.update_latest(|items| items.remove(index));
+            self.changes.emit(|| VecDiff::Remove { index });
+
+            value
+        })
+    }
+
+    /// Moves the item at `from` so that it ends up at `to`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either index is out of bounds.
+    pub fn move_item(&self, from: usize, to: usize) {
+        batch::batch(|| {
+            self.items.update_latest(|items| {
+                let value = items.remove(from);
+                items.insert(to, value);
+            });
+            self.changes.emit(|| VecDiff::Move { from, to });
+        })
+    }
+
+    /// Replaces the item at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set(&self, index: usize, value: T) {
+        batch::batch(|| {
+            self.items.update_latest(|items| items[index] = value);
+            self.changes.emit(|| VecDiff::Update {
+                index,
+                value: self.items.latest()[index].clone(),
+            });
+        })
+    }
+
+    /// Mutates the item at `index` in place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn update<R>(&self, index: usize, f: impl FnOnce(&mut T) -> R) -> R {
+        batch::batch(|| {
+            let result = self.items.update_latest(|items| f(&mut items[index]));
+            self.changes.emit(|| VecDiff::Update {
+                index,
+                value: self.items.latest()[index].clone(),
+            });
+
+            result
+        })
+    }
+
+    pub fn clear(&self) {
+        batch::batch(|| {
+            self.items.update_latest(Vec::clear);
+            self.changes.emit(|| VecDiff::Clear);
+        })
+    }
+}
+
+impl<T: Clone + 'static> Default for StateVec<T> {
+    fn default() -> Self {
+        StateVec::new()
+    }
+}
+
+impl<T: 'static> From<Vec<T>> for StateVec<T> {
+    fn from(items: Vec<T>) -> Self {
+        StateVec {
+            items: items.into(),
+            changes: Changes::new(),
+        }
+    }
+}
+
+/// A map, ordered by insertion, that tells its subscribers what changed instead of only
+/// republishing itself.
+///
+/// Like [`StateVec`], the whole map can be read and tracked, while
+/// [`StateMap::subscribe_diff`] receives a [`MapDiff`] for every change.
+pub struct StateMap<K, V> {
+    entries: State<LinkedHashMap<K, V>>,
+    changes: Rc<Changes<MapDiff<K, V>>>,
+}
+
+impl<K: Hash + Eq + Clone + 'static, V: Clone + 'static> StateMap<K, V> {
+    pub fn new() -> StateMap<K, V> {
+        StateMap {
+            entries: LinkedHashMap::new().into(),
+            changes: Changes::new(),
+        }
+    }
+
+    /// Registers `f` to be called with every change made to the map.
+    pub fn subscribe_diff(&self, f: impl FnMut(&MapDiff<K, V>) + 'static) -> Subscription {
+        self.changes.subscribe(f)
+    }
+
+    /// Registers `f` to be called with the whole map every time it changes.
+    pub fn subscribe(&self, f: impl FnMut(&LinkedHashMap<K, V>) + 'static) -> Subscription {
+        self.entries.subscribe(f)
+    }
+
+    /// Immutably borrows the map.
+    pub fn borrow(&self) -> Ref<'_, LinkedHashMap<K, V>> {
+        self.entries.borrow()
+    }
+
+    pub fn len(&self) -> usize {
+        self.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.borrow().is_empty()
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.borrow().contains_key(key)
+    }
+
+    /// Returns a clone of the value of `key`.
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.borrow().get(key).cloned()
+    }
+
+    /// Creates a read-only binding to the whole map.
+    pub fn bind(&self) -> ReadBinding<LinkedHashMap<K, V>> {
+        self.entries.bind()
+    }
+
+    /// Sets the value of `key`, returning the previous one.
+    ///
+    /// A new key is added at the end of the map, an existing one keeps its position.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        batch::batch(|| {
+            let changed = key.clone();
+            let previous = self
+                .entries
+                .update_latest(|entries| match entries.get_mut(&key) {
+                    Some(current) => Some(std::mem::replace(current, value)),
+                    None => entries.insert(key, value),
+                });
+
+            self.changes.emit(|| {
+                let value = self.entries.latest()[&changed].clone();
+                match previous {
+                    Some(_) => MapDiff::Update {
+                        key: changed,
+                        value,
+                    },
+                    None => MapDiff::Insert {
+                        key: changed,
+                        value,
+                    },
+                }
+            });
+
+            previous
+        })
+    }
+
+    /// Mutates the value of `key` in place, if it exists.
+    pub fn update<R>(&self, key: &K, f: impl FnOnce(&mut V) -> R) -> Option<R> {
+        if !self.entries.latest().contains_key(key) {
+            return None;
+        }
+
+        batch::batch(|| {
+            let result = self
+                .entries
+                .update_latest(|entries| entries.get_mut(key).map(f));
+            self.changes.emit(|| MapDiff::Update {
+                key: key.clone(),
+                value: self.entries.latest()[key].clone(),
+            });
+
+            result
+        })
+    }
+
+    pub fn remove(&self, key: &K) -> Option<V> {
+        if !self.entries.latest().contains_key(key) {
+            return None;
+        }
+
+        batch::batch(|| {
+            let value = self.entries.update_latest(|entries| entries.remove(key));
+            self.changes.emit(|| MapDiff::Remove { key: key.clone() });
+
+            value
+        })
+    }
+
+    pub fn clear(&self) {
+        batch::batch(|| {
+            self.entries.update_latest(LinkedHashMap::clear);
+            self.changes.emit(|| MapDiff::Clear);
+        })
+    }
+}
+
+impl<K: Hash + Eq + Clone + 'static, V: Clone + 'static> Default for StateMap<K, V> {
+    fn default() -> Self {
+        StateMap::new()
+    }
+}
@@ -1,4 +1,192 @@
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    marker::PhantomData,
+    rc::Rc,
+};
 
-pub struct AppConfig {
-    
-}
\ No newline at end of file
+use crate::{batch, provide_context, use_context, Memo, State, WriteBinding};
+
+/// The name and type of a slice of the [`AppConfig`] store.
+///
+/// ```ignore
+/// const TODOS: Slice<Vec<Todo>> = Slice::new("todos");
+/// ```
+pub struct Slice<S> {
+    name: &'static str,
+    marker: PhantomData<fn() -> S>,
+}
+
+impl<S> Slice<S> {
+    pub const fn new(name: &'static str) -> Slice<S> {
+        Slice {
+            name,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<S> Clone for Slice<S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for Slice<S> {}
+
+type Reducer = Rc<dyn Fn(Rc<dyn Any>)>;
+
+#[derive(Default)]
+struct Store {
+    slices: RefCell<HashMap<&'static str, Rc<dyn Any>>>,
+    reducers: RefCell<HashMap<TypeId, Vec<Reducer>>>,
+}
+
+/// The application store: shared state split into named slices, updated by dispatching
+/// actions to reducers.
+///
+/// The store is a cheap handle, usually provided once by the root view with
+/// [`AppConfig::provide`] and picked up anywhere below it with [`AppConfig::current`].
+///
+/// ```ignore
+/// const COUNT: Slice<u64> = Slice::new("count");
+/// struct Increment(u64);
+///
+/// let config = AppConfig::new();
+/// config.add_slice(COUNT, 0);
+/// config.add_reducer(COUNT, |count, Increment(by): &Increment| *count += by);
+///
+/// let even = config.select(COUNT, |count| count % 2 == 0);
+/// config.dispatch(Increment(2));
+/// ```
+#[derive(Clone, Default)]
+pub struct AppConfig(Rc<Store>);
+
+impl AppConfig {
+    pub fn new() -> AppConfig {
+        AppConfig::default()
+    }
+
+    /// Adds `slice` to the store with its initial value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a slice with the same name was already added.
+    pub fn add_slice<S: 'static>(&self, slice: Slice<S>, initial: S) {
+        let state: State<S> = initial.into();
+
+        let previous = self
+            .0
+            .slices
+            .borrow_mut()
+            .insert(slice.name, Rc::new(state));
+        assert!(
+            previous.is_none(),
+            "A slice named `{}` was already added to the store",
+            slice.name
+        );
+    }
+
+    /// Returns a binding to `slice`, through which it can be read or assigned directly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice wasn't added, or was added with a different type.
+    pub fn slice<S: 'static>(&self, slice: Slice<S>) -> WriteBinding<S> {
+        let slices = self.0.slices.borrow();
+        let state = slices
+            .get(slice.name)
+            .unwrap_or_else(|| panic!("No slice named `{}` was added to the store", slice.name));
+
+        state
+            .downcast_ref::<State<S>>()
+            .unwrap_or_else(|| {
+                panic!(
+                    "The slice named `{}` doesn't hold a `{}`",
+                    slice.name,
+                    std::any::type_name::<S>()
+                )
+            })
+            .bind_mut()
+    }
+
+    /// Derives a value from `slice`.
+    ///
+    /// Subscribers of the returned memo are only notified when the selected value changes,
+    /// not on every change to the slice.
+    pub fn select<S: 'static, U: PartialEq + 'static>(
+        &self,
+        slice: Slice<S>,
+        selector: impl Fn(&S) -> U + 'static,
+    ) -> Memo<U> {
+        let slice = self.slice(slice);
+
+        Memo::new(move || slice.with(&selector))
+    }
+
+    /// Registers `reducer` to update `slice` whenever an action of type `A` is dispatched.
+    pub fn add_reducer<S: 'static, A: 'static>(
+        &self,
+        slice: Slice<S>,
+        reducer: impl Fn(&mut S, &A) + 'static,
+    ) {
+        let slice = self.slice(slice);
+        let reducer = Rc::new(reducer);
+        let reducer: Reducer = Rc::new(move |action| {
+            let action = action
+                .downcast::<A>()
+                .unwrap_or_else(|_| panic!("Reducer was called with the wrong action"));
+            let reducer = reducer.clone();
+            slice.update(move |state| reducer(state, &action));
+        });
+
+        self.0
+            .reducers
+            .borrow_mut()
+            .entry(TypeId::of::<A>())
+            .or_default()
+            .push(reducer);
+    }
+
+    /// Runs every reducer registered for `A` on `action`.
+    ///
+    /// The reducers run in a single [`batch`](crate::batch()), so subscribers see the
+    /// result of the whole action at once.
+    pub fn dispatch<A: 'static>(&self, action: A) {
+        let reducers = self
+            .0
+            .reducers
+            .borrow()
+            .get(&TypeId::of::<A>())
+            .cloned()
+            .unwrap_or_default();
+
+        // Shared, since a reducer running while its slice is published is applied later.
+        let action: Rc<dyn Any> = Rc::new(action);
+        batch(|| {
+            for reducer in reducers {
+                reducer(action.clone());
+            }
+        });
+    }
+
+    /// Makes the store available to [`AppConfig::current`] in the view being created and
+    /// every view nested inside of it.
+    pub fn provide(&self) {
+        provide_context(self.clone());
+    }
+
+    /// Returns the store provided by an enclosing view.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no enclosing view provides a store.
+    pub fn current() -> AppConfig {
+        use_context()
+    }
+}
@@ -0,0 +1,100 @@
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+};
+
+/// The contexts provided by a scope, chained to the ones provided by its ancestors.
+#[derive(Default)]
+pub(crate) struct Contexts {
+    values: RefCell<HashMap<TypeId, Rc<dyn Any>>>,
+    parent: Option<Rc<Contexts>>,
+}
+
+impl Contexts {
+    pub(crate) fn child(parent: &Rc<Contexts>) -> Rc<Contexts> {
+        Rc::new(Contexts {
+            values: RefCell::default(),
+            parent: Some(parent.clone()),
+        })
+    }
+
+    fn get(&self, id: TypeId) -> Option<Rc<dyn Any>> {
+        match self.values.borrow().get(&id) {
+            Some(value) => Some(value.clone()),
+            None => self.parent.as_ref()?.get(id),
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Vec<Rc<Contexts>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs `f` with `contexts` as the target of [`provide_context`] and [`use_context`].
+pub(crate) fn enter<R>(contexts: Rc<Contexts>, f: impl FnOnce() -> R) -> R {
+    /// Leaves the contexts when dropped, including when `f` panicked.
+    struct Leave;
+
+    impl Drop for Leave {
+        fn drop(&mut self) {
+            let _ = CURRENT.try_with(|current| current.borrow_mut().pop());
+        }
+    }
+
+    CURRENT.with(|current| current.borrow_mut().push(contexts));
+    let _leave = Leave;
+
+    f()
+}
+
+fn current() -> Option<Rc<Contexts>> {
+    CURRENT.with(|current| current.borrow().last().cloned())
+}
+
+/// Makes `value` available to [`use_context`] in the view being created and every view
+/// nested inside of it.
+///
+/// Providing a value of a type that the view already provides replaces it.
+///
+/// # Panics
+///
+/// Panics if called outside of a view's `new` or `on_init`.
+pub fn provide_context<T: 'static>(value: T) {
+    let contexts = current().unwrap_or_else(|| {
+        panic!(
+            "`provide_context::<{}>` was called outside of a view",
+            std::any::type_name::<T>()
+        )
+    });
+
+    contexts
+        .values
+        .borrow_mut()
+        .insert(TypeId::of::<T>(), Rc::new(value));
+}
+
+/// Returns the value of type `T` provided by the closest enclosing view, if there is one.
+pub fn try_use_context<T: Clone + 'static>() -> Option<T> {
+    let value = current()?.get(TypeId::of::<T>())?;
+
+    value.downcast_ref::<T>().cloned()
+}
+
+/// Returns the value of type `T` provided by the closest enclosing view.
+///
+/// Contexts are usually cheap handles such as a binding or an `Rc`, since the value is
+/// cloned out of the view that provides it.
+///
+/// # Panics
+///
+/// Panics if no enclosing view provides a `T`.
+pub fn use_context<T: Clone + 'static>() -> T {
+    try_use_context().unwrap_or_else(|| {
+        panic!(
+            "No context of type `{}` was provided by an enclosing view, call `provide_context` in one of its ancestors",
+            std::any::type_name::<T>()
+        )
+    })
+}
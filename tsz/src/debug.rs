@@ -0,0 +1,215 @@
+//! Inspecting the reactive graph.
+//!
+//! Every state can carry a name set with [`InnerState::set_debug_name`], and every subscriber
+//! remembers the [`Origin`] it was created from, e.g. the text node of a view that displays
+//! the state. [`dump_graph`] renders the states that are alive together with their
+//! subscribers, and [`graph`] returns the same tree for plain [`TreeDisplay::format`]ting,
+//! e.g. for the browser console which doesn't understand terminal colors.
+//!
+//! Only debug builds record the graph; with `debug_assertions` off it is always empty.
+//!
+//! ```ignore
+//! let count: State<u32> = 0.into();
+//! count.set_debug_name("count");
+//! println!("{}", tsz::debug::dump_graph());
+//! ```
+
+use std::{
+    cell::RefCell,
+    fmt,
+    rc::{Rc, Weak},
+};
+
+use crate::{
+    format::{Config, NodeDisplay, SemanticType, TreeDisplay},
+    InnerState,
+};
+
+/// Where a subscriber was created: the view and the element within it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Origin {
+    pub view: &'static str,
+    pub element: &'static str,
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::{}", self.view, self.element)
+    }
+}
+
+thread_local! {
+    static ORIGINS: RefCell<Vec<Origin>> = const { RefCell::new(Vec::new()) };
+    static STATES: RefCell<Vec<Weak<dyn Inspect>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs `f`, tagging every subscriber it registers with `origin`.
+pub fn with_origin<R>(origin: Origin, f: impl FnOnce() -> R) -> R {
+    if !cfg!(debug_assertions) {
+        return f();
+    }
+
+    struct Pop;
+
+    impl Drop for Pop {
+        fn drop(&mut self) {
+            ORIGINS.with(|origins| origins.borrow_mut().pop());
+        }
+    }
+
+    ORIGINS.with(|origins| origins.borrow_mut().push(origin));
+    let _pop = Pop;
+
+    f()
+}
+
+pub(crate) fn current_origin() -> Option<Origin> {
+    ORIGINS.with(|origins| origins.borrow().last().copied())
+}
+
+/// A state as seen by the inspector.
+pub(crate) trait Inspect {
+    fn debug_name(&self) -> Option<String>;
+
+    fn type_name(&self) -> &'static str;
+
+    fn origins(&self) -> Vec<Option<Origin>>;
+}
+
+impl<T> Inspect for InnerState<T> {
+    fn debug_name(&self) -> Option<String> {
+        self.name.borrow().clone()
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn origins(&self) -> Vec<Option<Origin>> {
+        self.subscribers.origins()
+    }
+}
+
+/// Makes `state` visible to [`graph`] for as long as it is alive.
+pub(crate) fn register(state: Weak<dyn Inspect>) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+
+    STATES.with(|states| {
+        let mut states = states.borrow_mut();
+        // Dropped states are pruned before the list grows, which keeps it amortized.
+        if states.len() == states.capacity() {
+            states.retain(|state| state.strong_count() > 0);
+        }
+        states.push(state);
+    })
+}
+
+/// The states that are alive on this thread, in the order they were created.
+pub struct Graph(Vec<StateNode>);
+
+/// A state and the subscribers it notifies.
+pub struct StateNode {
+    name: Option<String>,
+    type_name: &'static str,
+    subscribers: Vec<SubscriberNode>,
+}
+
+/// A subscriber of a state, and where it was created if known.
+pub struct SubscriberNode(Option<Origin>);
+
+/// Collects the current reactive graph.
+pub fn graph() -> Graph {
+    let states = STATES.with(|states| {
+        let mut states = states.borrow_mut();
+        states.retain(|state| state.strong_count() > 0);
+        states.clone()
+    });
+
+    Graph(
+        states
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|state: Rc<dyn Inspect>| StateNode {
+                name: state.debug_name(),
+                type_name: state.type_name(),
+                subscribers: state.origins().into_iter().map(SubscriberNode).collect(),
+            })
+            .collect(),
+    )
+}
+
+/// Renders the current reactive graph as a tree, colored by [`TreeDisplay::semantic_format`].
+pub fn dump_graph() -> String {
+    graph().semantic_format()
+}
+
+impl NodeDisplay for Graph {
+    fn fmt(&self, f: &mut fmt::Formatter, _cfg: &Config) -> fmt::Result {
+        write!(f, "Reactive graph ({} states)", self.0.len())
+    }
+}
+
+impl TreeDisplay for Graph {
+    fn num_children(&self, _cfg: &Config) -> usize {
+        self.0.len()
+    }
+
+    fn child_at(&self, index: usize, _cfg: &Config) -> Option<&dyn TreeDisplay> {
+        Some(&self.0[index])
+    }
+
+    fn semantic_type(&self) -> SemanticType {
+        SemanticType::Module
+    }
+}
+
+impl NodeDisplay for StateNode {
+    fn fmt(&self, f: &mut fmt::Formatter, _cfg: &Config) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{name}: {}", self.type_name),
+            None => write!(f, "<unnamed>: {}", self.type_name),
+        }
+    }
+}
+
+impl TreeDisplay for StateNode {
+    fn num_children(&self, _cfg: &Config) -> usize {
+        self.subscribers.len()
+    }
+
+    fn child_at(&self, index: usize, _cfg: &Config) -> Option<&dyn TreeDisplay> {
+        Some(&self.subscribers[index])
+    }
+
+    fn semantic_type(&self) -> SemanticType {
+        SemanticType::Variable
+    }
+}
+
+impl NodeDisplay for SubscriberNode {
+    fn fmt(&self, f: &mut fmt::Formatter, _cfg: &Config) -> fmt::Result {
+        match &self.0 {
+            Some(origin) => write!(f, "{origin}"),
+            None => f.write_str("<anonymous>"),
+        }
+    }
+}
+
+impl TreeDisplay for SubscriberNode {
+    fn num_children(&self, _cfg: &Config) -> usize {
+        0
+    }
+
+    fn child_at(&self, _index: usize, _cfg: &Config) -> Option<&dyn TreeDisplay> {
+        None
+    }
+
+    fn semantic_type(&self) -> SemanticType {
+        match self.0 {
+            Some(_) => SemanticType::Function,
+            None => SemanticType::Default,
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+};
+
+use crate::{
+    batch::{self, Job, JobId},
+    debug::{self, Origin},
+    tracking::{self, Dependencies},
+};
+
+type Cleanups = Vec<Box<dyn FnOnce()>>;
+
+thread_local! {
+    static CLEANUPS: RefCell<Vec<Cleanups>> = const { RefCell::new(Vec::new()) };
+}
+
+struct EffectInner {
+    id: JobId,
+    this: Weak<EffectInner>,
+    f: RefCell<Box<dyn FnMut()>>,
+    cleanups: RefCell<Cleanups>,
+    dependencies: RefCell<Dependencies>,
+    disposed: Cell<bool>,
+    /// Where the effect was created, which tags the subscriptions of every run.
+    origin: Option<Origin>,
+}
+
+impl EffectInner {
+    fn execute(&self) {
+        if self.disposed.get() {
+            return;
+        }
+
+        self.cleanup();
+
+        /// Keeps the callbacks registered by the run, including when it panicked, so they
+        /// still run and later ones don't land in a frame nobody pops.
+        struct Collect<'a>(&'a EffectInner);
+
+        impl Drop for Collect<'_> {
+            fn drop(&mut self) {
+                let cleanups = CLEANUPS.try_with(|cleanups| cleanups.borrow_mut().pop());
+                if let Ok(Some(cleanups)) = cleanups {
+                    self.0.cleanups.replace(cleanups);
+                }
+            }
+        }
+
+        CLEANUPS.with(|cleanups| cleanups.borrow_mut().push(Vec::new()));
+        let _collect = Collect(self);
+
+        let previous = self.dependencies.take();
+        let track = || {
+            tracking::track(self.id, self.this.clone(), previous, || {
+                let mut f = self.f.borrow_mut();
+                f()
+            })
+        };
+        let ((), dependencies) = match self.origin {
+            Some(origin) => debug::with_origin(origin, track),
+            None => track(),
+        };
+        self.dependencies.replace(dependencies);
+    }
+
+    fn cleanup(&self) {
+        for cleanup in self.cleanups.take() {
+            cleanup();
+        }
+    }
+
+    fn dispose(&self) {
+        self.disposed.set(true);
+
+        let dependencies = self.dependencies.take();
+        drop(dependencies);
+
+        self.cleanup();
+    }
+}
+
+impl Job for EffectInner {
+    fn run(&self) {
+        self.execute();
+    }
+}
+
+/// A handle to a running effect.
+///
+/// Dropping the handle disposes the effect: it stops reacting to changes and its pending
+/// cleanup callbacks are run. Use [`Scope::own_effect`](crate::Scope::own_effect) to tie an
+/// effect to the view that created it.
+#[must_use = "dropping an `Effect` immediately disposes it"]
+pub struct Effect(Rc<EffectInner>);
+
+impl Effect {
+    /// Stops the effect and runs its pending cleanup callbacks.
+    pub fn dispose(self) {
+        drop(self)
+    }
+
+    /// Detaches the handle, leaving the effect running for the rest of the program.
+    pub fn forget(self) {
+        std::mem::forget(self)
+    }
+}
+
+impl Drop for Effect {
+    fn drop(&mut self) {
+        self.0.dispose();
+    }
+}
+
+/// Runs `f` immediately and again every time one of the states it read changes.
+///
+/// Callbacks registered with [`on_cleanup`] while `f` runs are called before the next run
+/// and when the effect is disposed.
+pub fn effect(f: impl FnMut() + 'static) -> Effect {
+    let inner = Rc::new_cyclic(|this| EffectInner {
+        id: JobId::new(),
+        this: this.clone(),
+        f: RefCell::new(Box::new(f)),
+        cleanups: RefCell::new(Vec::new()),
+        dependencies: RefCell::new(Dependencies::default()),
+        disposed: Cell::new(false),
+        origin: debug::current_origin(),
+    });
+
+    // Like its later runs, the first run is batched, so an effect that writes to a state it
+    // reads runs again once it returned instead of re-entering itself.
+    batch::run(inner.id, || inner.execute());
+
+    Effect(inner)
+}
+
+/// Registers `f` to be called before the running effect runs again, or when it is disposed.
+///
+/// Outside of an effect the callback is dropped without being called.
+pub fn on_cleanup(f: impl FnOnce() + 'static) {
+    CLEANUPS.with(|cleanups| {
+        if let Some(frame) = cleanups.borrow_mut().last_mut() {
+            frame.push(Box::new(f));
+        }
+    })
+}
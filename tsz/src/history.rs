@@ -0,0 +1,175 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    rc::Rc,
+    time::Duration,
+};
+
+use crate::{clock, ReadBinding, State, Subscription};
+
+struct History<T> {
+    /// The value the state held before each recorded change, oldest first.
+    undo: RefCell<VecDeque<T>>,
+    redo: RefCell<Vec<T>>,
+    /// The last value seen by the history.
+    current: RefCell<T>,
+    capacity: usize,
+    coalesce: Cell<Option<Duration>>,
+    last_change: Cell<Option<Duration>>,
+    can_undo: State<bool>,
+    can_redo: State<bool>,
+}
+
+impl<T: Clone + PartialEq + 'static> History<T> {
+    fn record(&self, value: &T) {
+        if *self.current.borrow() == *value {
+            return;
+        }
+
+        let previous = self.current.replace(value.clone());
+        let time = clock::clock().now();
+        let coalesced = self.coalesce.get().is_some_and(|window| {
+            self.last_change
+                .get()
+                .is_some_and(|last| time.saturating_sub(last) < window)
+                && self.redo.borrow().is_empty()
+                && !self.undo.borrow().is_empty()
+        });
+        self.last_change.set(Some(time));
+
+        // A change coalesced into the previous one keeps that entry's starting point.
+        if !coalesced {
+            let mut undo = self.undo.borrow_mut();
+            undo.push_back(previous);
+            if undo.len() > self.capacity {
+                undo.pop_front();
+            }
+        }
+
+        self.redo.borrow_mut().clear();
+        self.update_flags();
+    }
+
+    fn update_flags(&self) {
+        self.can_undo
+            .value_mut()
+            .assign(!self.undo.borrow().is_empty());
+        self.can_redo
+            .value_mut()
+            .assign(!self.redo.borrow().is_empty());
+    }
+}
+
+/// A state that remembers its previous values so changes can be undone and redone.
+///
+/// Every publish of a different value is one history entry, so all of the assignments made
+/// in one [`batch`](crate::batch()), such as a single event handler, are undone together.
+/// The history derefs to the [`State`] it tracks, which is read and written as usual.
+///
+/// ```ignore
+/// let text = HistoryState::new(String::new()).coalesce_within(Duration::from_millis(500));
+/// text.value_mut().assign("hello".to_string());
+/// text.undo();
+/// ```
+pub struct HistoryState<T> {
+    state: State<T>,
+    history: Rc<History<T>>,
+    _subscription: Subscription,
+}
+
+impl<T: Clone + PartialEq + 'static> HistoryState<T> {
+    /// Creates a history that keeps the last 100 changes.
+    pub fn new(value: T) -> HistoryState<T> {
+        HistoryState::with_capacity(value, 100)
+    }
+
+    /// Creates a history that keeps the last `capacity` changes.
+    pub fn with_capacity(value: T, capacity: usize) -> HistoryState<T> {
+        let history = Rc::new(History {
+            undo: RefCell::new(VecDeque::new()),
+            redo: RefCell::new(Vec::new()),
+            current: RefCell::new(value.clone()),
+            capacity,
+            coalesce: Cell::new(None),
+            last_change: Cell::new(None),
+            can_undo: false.into(),
+            can_redo: false.into(),
+        });
+
+        let state: State<T> = value.into();
+        let subscription = state.subscribe({
+            let history = history.clone();
+            move |value| history.record(value)
+        });
+
+        HistoryState {
+            state,
+            history,
+            _subscription: subscription,
+        }
+    }
+
+    /// Merges changes made less than `window` apart into a single entry, e.g. the
+    /// keystrokes of a word being typed. Time is read from the [`clock`](clock::clock).
+    pub fn coalesce_within(self, window: Duration) -> HistoryState<T> {
+        self.history.coalesce.set(Some(window));
+        self
+    }
+
+    /// Restores the value before the last change. Returns `false` if there is nothing to undo.
+    pub fn undo(&self) -> bool {
+        let Some(previous) = self.history.undo.borrow_mut().pop_back() else {
+            return false;
+        };
+
+        let current = self.history.current.replace(previous.clone());
+        self.history.redo.borrow_mut().push(current);
+        self.restore(previous);
+
+        true
+    }
+
+    /// Reapplies the last undone change. Returns `false` if there is nothing to redo.
+    pub fn redo(&self) -> bool {
+        let Some(next) = self.history.redo.borrow_mut().pop() else {
+            return false;
+        };
+
+        let current = self.history.current.replace(next.clone());
+        self.history.undo.borrow_mut().push_back(current);
+        self.restore(next);
+
+        true
+    }
+
+    /// Forgets every recorded change, keeping the current value.
+    pub fn clear_history(&self) {
+        self.history.undo.borrow_mut().clear();
+        self.history.redo.borrow_mut().clear();
+        self.history.update_flags();
+    }
+
+    pub fn can_undo(&self) -> ReadBinding<bool> {
+        self.history.can_undo.bind()
+    }
+
+    pub fn can_redo(&self) -> ReadBinding<bool> {
+        self.history.can_redo.bind()
+    }
+
+    fn restore(&self, value: T) {
+        // The history already moved to `value`, so publishing it doesn't record a change, and
+        // the next edit always starts a new entry.
+        self.history.last_change.set(None);
+        self.history.update_flags();
+        self.state.value_mut().assign(value);
+    }
+}
+
+impl<T> std::ops::Deref for HistoryState<T> {
+    type Target = State<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state
+    }
+}
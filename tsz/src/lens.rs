@@ -0,0 +1,83 @@
+use std::{cell::Ref, rc::Rc};
+
+use crate::{
+    source::{Sink, Source},
+    Equality, Subscription,
+};
+
+/// A read-only view of a field of the value held by `parent`.
+pub(crate) struct ReadLens<P, U> {
+    pub(crate) parent: Rc<dyn Source<P>>,
+    pub(crate) get: Rc<dyn Fn(&P) -> &U>,
+}
+
+impl<P: 'static, U: 'static> Source<U> for ReadLens<P, U> {
+    fn borrow(&self) -> Ref<'_, U> {
+        Ref::map(self.parent.borrow(), |parent| (self.get)(parent))
+    }
+
+    fn subscribe(&self, mut f: Box<dyn FnMut(&U)>) -> Subscription {
+        let get = self.get.clone();
+        self.parent
+            .subscribe(Box::new(move |parent| f(get(parent))))
+    }
+}
+
+/// A view of a field of the value held by `parent`, through which the field can be written.
+///
+/// Writes go through the parent, which publishes to its own subscribers as well as the
+/// ones subscribed through the lens.
+pub(crate) struct WriteLens<P, U> {
+    pub(crate) parent: Rc<dyn Sink<P>>,
+    pub(crate) get: Rc<dyn Fn(&P) -> &U>,
+    pub(crate) get_mut: Rc<dyn Fn(&mut P) -> &mut U>,
+}
+
+impl<P: 'static, U: 'static> Source<U> for WriteLens<P, U> {
+    fn borrow(&self) -> Ref<'_, U> {
+        Ref::map(self.parent.borrow(), |parent| (self.get)(parent))
+    }
+
+    fn subscribe(&self, mut f: Box<dyn FnMut(&U)>) -> Subscription {
+        let get = self.get.clone();
+        self.parent
+            .subscribe(Box::new(move |parent| f(get(parent))))
+    }
+}
+
+impl<P: Clone + 'static, U: 'static> Sink<U> for WriteLens<P, U> {
+    fn latest(&self) -> Option<Ref<'_, U>> {
+        let parent = self.parent.latest()?;
+        Some(Ref::map(parent, |parent| (self.get)(parent)))
+    }
+
+    fn settle(&self)
+    where
+        U: Clone,
+    {
+        self.parent.settle();
+    }
+
+    fn equality(&self) -> Option<&Equality<U>> {
+        None
+    }
+
+    fn set(&self, value: U) {
+        self.modify(Box::new(move |field| *field = value));
+    }
+
+    fn modify(&self, f: Box<dyn FnOnce(&mut U)>) {
+        // While the parent is being published, the field is written to a copy of it, so the
+        // write can be read back through the lens right away.
+        self.parent.settle();
+
+        let get_mut = self.get_mut.clone();
+        self.parent
+            .modify(Box::new(move |parent| f(get_mut(parent))));
+    }
+
+    fn modifies_now(&self) -> bool {
+        // Writes settle the parent first, so they are never queued.
+        true
+    }
+}
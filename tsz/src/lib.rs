@@ -1,10 +1,48 @@
+mod assign;
+mod attribute;
+mod batch;
+mod collections;
+mod context;
+pub mod debug;
+mod effect;
+mod history;
+mod lens;
+mod listener;
+mod memo;
+mod persist;
 mod refs;
+mod resource;
+mod scope;
+mod snapshot;
+mod source;
+mod subscription;
+mod timing;
+mod tracking;
 use std::{
-    cell::{Cell, RefCell},
-    rc::Rc,
+    cell::{Cell, Ref, RefCell, RefMut},
+    rc::{Rc, Weak},
 };
 
+pub use attribute::*;
+pub use batch::batch;
+pub use collections::*;
+pub use context::{provide_context, try_use_context, use_context};
+pub use effect::*;
+pub use history::*;
+pub use listener::*;
+pub use memo::*;
+pub use persist::*;
 pub use refs::*;
+pub use resource::*;
+pub use scope::*;
+pub use snapshot::{Restore, Snapshot};
+pub use subscription::*;
+pub use tracking::untrack;
+
+use batch::{Job, JobId};
+use lens::{ReadLens, WriteLens};
+use source::{Sink, Source};
+use subscription::Subscribers;
 
 pub mod format;
 
@@ -13,6 +51,15 @@ pub mod html;
 pub use tsz_macros::*;
 pub mod views;
 pub mod config;
+pub mod task;
+pub mod clock;
+
+#[doc(hidden)]
+pub use assign::*;
+#[doc(hidden)]
+pub use serde as __serde;
+#[doc(hidden)]
+pub use serde_json as __serde_json;
 
 #[doc(hidden)]
 #[macro_export]
@@ -22,97 +69,399 @@ macro_rules! __util_format_args {
     };
 }
 
-pub struct StateRefMut<'a, T>(pub &'a InnerState<T>);
+pub struct StateRefMut<'a, T>(&'a dyn Sink<T>);
 
 macro_rules! impl_op {
-    ($tr:ident, $name:ident) => {
-        impl<T: std::ops::$tr<Output = T> + Copy> StateRefMut<'_, T> {
-            pub fn $name(self, rhs: T) {
-                let new_value = self.0.value.get().$name(rhs);
-                self.0.value.set(new_value);
+    ($tr:ident, $name:ident, $assign:ident) => {
+        impl<T: 'static> StateRefMut<'_, T> {
+            /// Applies the operator to the value, publishing the result if it changed.
+            ///
+            /// Types that aren't `PartialEq` or `Clone` can use [`StateRefMut::update`].
+            pub fn $name<R>(self, rhs: R)
+            where
+                T: std::ops::$tr<R> + PartialEq + Clone,
+            {
+                self.update_if_changed(move |value| value.$assign(rhs));
+            }
+        }
 
-                self.0.publish();
+        impl<T, R> std::ops::$tr<R> for StateRefMut<'_, T>
+        where
+            T: std::ops::$tr<R> + PartialEq + Clone + 'static,
+        {
+            fn $assign(&mut self, rhs: R) {
+                StateRefMut(self.0).$name(rhs);
             }
         }
     };
 }
 
-impl_op!(Add, add);
-impl_op!(Sub, sub);
-impl_op!(Mul, mul);
-impl_op!(Div, div);
-impl_op!(Rem, rem);
-impl_op!(BitAnd, bitand);
-impl_op!(BitOr, bitor);
-impl_op!(BitXor, bitxor);
-impl_op!(Shl, shl);
-impl_op!(Shr, shr);
+impl_op!(AddAssign, add, add_assign);
+impl_op!(SubAssign, sub, sub_assign);
+impl_op!(MulAssign, mul, mul_assign);
+impl_op!(DivAssign, div, div_assign);
+impl_op!(RemAssign, rem, rem_assign);
+impl_op!(BitAndAssign, bitand, bitand_assign);
+impl_op!(BitOrAssign, bitor, bitor_assign);
+impl_op!(BitXorAssign, bitxor, bitxor_assign);
+impl_op!(ShlAssign, shl, shl_assign);
+impl_op!(ShrAssign, shr, shr_assign);
+
+impl<T: 'static> StateRefMut<'_, T> {
+    /// Replaces the value, publishing it only if it differs from the current one.
+    ///
+    /// The state's own equality is used if it has one, `PartialEq` otherwise. Types that
+    /// aren't `PartialEq` can use [`StateRefMut::set`].
+    pub fn assign(self, rhs: T)
+    where
+        T: PartialEq,
+    {
+        // Values updated in place by a subscriber can't be compared until the update is applied.
+        let unchanged = match (self.0.equality(), self.0.latest()) {
+            (Some(equals), Some(latest)) => equals(&latest, &rhs),
+            (None, Some(latest)) => *latest == rhs,
+            (_, None) => false,
+        };
+
+        if !unchanged {
+            self.set_force(rhs);
+        }
+    }
+
+    /// Replaces the value, publishing it unless the state's equality finds it unchanged.
+    ///
+    /// Unlike [`StateRefMut::assign`], this works for any type, so states created without
+    /// [`State::with_equality`] publish every value.
+    pub fn set(self, rhs: T) {
+        let unchanged = match (self.0.equality(), self.0.latest()) {
+            (Some(equals), Some(latest)) => equals(&latest, &rhs),
+            _ => false,
+        };
+
+        if !unchanged {
+            self.set_force(rhs);
+        }
+    }
+
+    /// Replaces the value and publishes it, even if it is equal to the current one.
+    ///
+    /// When called from one of the state's own subscribers, the value is stored and
+    /// published again once every subscriber has seen the current one.
+    pub fn set_force(self, rhs: T) {
+        self.0.set(rhs);
+    }
+
+    /// Mutates the value in place, e.g. to push onto a `Vec`, and publishes the result.
+    ///
+    /// The result is published even if `f` leaves the value unchanged. As with
+    /// [`InnerState::update`], an update made by one of the state's own subscribers is
+    /// applied once every subscriber has seen the current value.
+    pub fn update(self, f: impl FnOnce(&mut T) + 'static) {
+        self.0.modify(Box::new(f));
+    }
 
-impl<T: Copy> StateRefMut<'_, T> {
-    pub fn assign(self, rhs: T) {
-        self.0.value.set(rhs);
+    /// Mutates a copy of the value, then assigns it, so it is only published if it changed.
+    pub fn update_if_changed(self, f: impl FnOnce(&mut T))
+    where
+        T: PartialEq + Clone,
+    {
+        self.0.settle();
+        let mut value = self
+            .0
+            .latest()
+            .expect("Settled state still has queued updates")
+            .clone();
+        f(&mut value);
 
-        self.0.publish();
+        self.assign(value);
+    }
+
+    /// Replaces the value and publishes it, returning the previous one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from one of the state's own subscribers before anything else was
+    /// written to it, since they still hold the previous value. Use
+    /// [`StateRefMut::replace_latest`] there.
+    pub fn replace(self, value: T) -> T {
+        match self.0.swap(value) {
+            Ok(previous) => previous,
+            Err(_) => panic!(
+                "`replace` was called from a subscriber of the state, which still holds the \
+                 value; use `replace_latest` instead"
+            ),
+        }
+    }
+
+    /// Like [`StateRefMut::replace`], but also works from the state's own subscribers by
+    /// copying the value they hold, returning it with any updates they already made.
+    pub fn replace_latest(self, value: T) -> T
+    where
+        T: Clone,
+    {
+        self.0.settle();
+        self.replace(value)
+    }
+
+    /// Takes the value out, leaving the default in its place, and publishes it.
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`StateRefMut::replace`].
+    pub fn take(self) -> T
+    where
+        T: Default,
+    {
+        self.replace(T::default())
     }
 }
 
-impl<'a, T: std::ops::Add<Output = T> + Copy> std::ops::AddAssign<T> for StateRefMut<'a, T> {
-    fn add_assign(&mut self, rhs: T) {
-        let new_value = self.0.value.get() + rhs;
-        self.0.value.set(new_value);
+impl<T: PartialEq + Clone + 'static> StateRefMut<'_, T> {
+    /// Negates the value, as with `-value`, publishing the result if it changed.
+    pub fn negate(self)
+    where
+        T: std::ops::Neg<Output = T>,
+    {
+        self.update_if_changed(|value| *value = -value.clone());
+    }
+
+    /// Inverts the value, as with `!value`, publishing the result if it changed.
+    pub fn invert(self)
+    where
+        T: std::ops::Not<Output = T>,
+    {
+        self.update_if_changed(|value| *value = !value.clone());
+    }
+}
 
-        self.0.publish();
+impl StateRefMut<'_, bool> {
+    /// Flips the flag.
+    pub fn toggle(self) {
+        self.update(|value| *value = !*value);
     }
 }
 
+type Equality<T> = Box<dyn Fn(&T, &T) -> bool>;
+type Update<T> = Box<dyn FnOnce(&mut T)>;
+
 pub struct InnerState<T> {
-    pub value: Cell<T>,
-    subscribers: RefCell<Vec<Box<dyn FnMut(&T)>>>,
+    id: JobId,
+    this: Weak<InnerState<T>>,
+    pub(crate) value: RefCell<T>,
+    subscribers: Rc<Subscribers<T>>,
+    equals: Option<Equality<T>>,
+    dispatching: Cell<bool>,
+    /// A value written by a subscriber while the state was being dispatched.
+    pending: RefCell<Option<T>>,
+    /// Updates made in place by a subscriber while nothing was pending.
+    queued: RefCell<Vec<Update<T>>>,
+    name: RefCell<Option<String>>,
 }
 
-impl<T: Copy> InnerState<T> {
-    pub fn subscribe(&self, f: impl FnMut(&T) + 'static) {
-        let mut subs = self.subscribers.borrow_mut();
-        subs.push(Box::new(f));
+impl<T: 'static> InnerState<T> {
+    /// Registers `f` to be called every time the state is published.
+    ///
+    /// The callback stays subscribed for as long as the returned [`Subscription`] is alive.
+    ///
+    /// Subscribing from inside a publish is allowed; the new callback is first called on the
+    /// next publish.
+    pub fn subscribe(&self, f: impl FnMut(&T) + 'static) -> Subscription {
+        let key = self.subscribers.insert(Box::new(f));
+        let subscribers = Rc::downgrade(&self.subscribers);
+
+        Subscription::new(subscribers, key)
+    }
+
+    /// Immutably borrows the current value.
+    ///
+    /// The returned guard must be dropped before the state is written to again.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        tracking::record(self);
+        self.value.borrow()
+    }
+
+    /// Calls `f` with a reference to the current value.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.borrow())
     }
 
+    /// Notifies every subscriber of the current value.
+    ///
+    /// Inside of a [`batch()`] the notification is deferred until the batch ends.
     pub fn publish(&self) {
-        let mut subs = self.subscribers.borrow_mut();
+        // A state that isn't shared through an `Rc` can't be queued, so it notifies right away.
+        if self.this.strong_count() == 0 {
+            batch::run(self.id(), || self.dispatch());
+        } else {
+            batch::schedule(self.id(), self.this.clone());
+        }
+    }
 
-        for sub in subs.iter_mut() {
-            let value = self.value.get();
-            sub(&value)
+    /// Calls the subscribers in the order they subscribed.
+    ///
+    /// Writes made by a subscriber are applied and published once the dispatch is over.
+    fn dispatch(&self) {
+        {
+            let _dispatching = Dispatching::start(self);
+            self.subscribers.notify(&self.value.borrow());
         }
+
+        let pending = self.pending.take();
+        let queued = self.queued.take();
+        if pending.is_none() && queued.is_empty() {
+            return;
+        }
+
+        {
+            let mut value = self.value.borrow_mut();
+            if let Some(pending) = pending {
+                *value = pending;
+            }
+            for f in queued {
+                f(&mut value);
+            }
+        }
+
+        self.publish();
     }
 
-    pub fn value(&self) -> T {
-        self.value.get()
+    /// Mutates the value in place and publishes the result to every subscriber.
+    ///
+    /// The result is published even if `f` leaves the value unchanged. When called from one
+    /// of the state's own subscribers, which still hold the current value, `f` runs once
+    /// every subscriber has seen it.
+    pub fn update(&self, f: impl FnOnce(&mut T) + 'static) {
+        Sink::modify(self, Box::new(f));
     }
 
     pub fn value_mut(&self) -> StateRefMut<T> {
         StateRefMut(self)
     }
 
-    // pub fn set_value(&self, new_val: T) {
-    //     *self.value.borrow_mut() = new_val;
-    //     self.publish();
-    // }
+    /// Names the state in the [`debug`] graph.
+    pub fn set_debug_name(&self, name: impl Into<String>) {
+        self.name.replace(Some(name.into()));
+    }
+
+    pub fn debug_name(&self) -> Option<String> {
+        self.name.borrow().clone()
+    }
+
+    /// Identifies the state, without ever being reused by another state.
+    pub(crate) fn id(&self) -> JobId {
+        self.id
+    }
+}
+
+impl<T: Copy + 'static> InnerState<T> {
+    pub fn value(&self) -> T {
+        *self.borrow()
+    }
+}
+
+impl<T: Clone + 'static> InnerState<T> {
+    /// Returns a clone of the current value.
+    pub fn cloned(&self) -> T {
+        self.borrow().clone()
+    }
+
+    /// Like [`InnerState::update`], but returns what `f` returns, even when called from one
+    /// of the state's own subscribers, by updating a copy published after them.
+    pub(crate) fn update_latest<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        Sink::settle(self);
+        if let Ok(mut pending) = RefMut::filter_map(self.pending.borrow_mut(), Option::as_mut) {
+            return f(&mut pending);
+        }
+
+        let result = f(&mut self.value.borrow_mut());
+        self.publish();
+
+        result
+    }
+
+    /// Borrows the value the state will hold once the pending writes are applied.
+    pub(crate) fn latest(&self) -> Ref<'_, T> {
+        Sink::settle(self);
+        Sink::latest(self).expect("Settled state still has queued updates")
+    }
+}
+
+impl<T: 'static> Job for InnerState<T> {
+    fn run(&self) {
+        self.dispatch();
+    }
 }
 
 impl<T> From<T> for InnerState<T> {
     fn from(value: T) -> Self {
         InnerState {
-            value: Cell::new(value),
-            subscribers: RefCell::new(Vec::new()),
+            id: JobId::new(),
+            this: Weak::new(),
+            value: RefCell::new(value),
+            subscribers: Rc::new(Subscribers::new()),
+            equals: None,
+            dispatching: Cell::new(false),
+            pending: RefCell::new(None),
+            queued: RefCell::new(Vec::new()),
+            name: RefCell::new(None),
+        }
+    }
+}
+
+/// Marks a state as dispatching until dropped, even if a subscriber panics.
+struct Dispatching<'a, T>(&'a InnerState<T>);
+
+impl<'a, T> Dispatching<'a, T> {
+    fn start(state: &'a InnerState<T>) -> Self {
+        state.dispatching.set(true);
+        Dispatching(state)
+    }
+}
+
+impl<T> Drop for Dispatching<'_, T> {
+    fn drop(&mut self) {
+        self.0.dispatching.set(false);
+
+        // Writes made before a subscriber panicked are dropped along with the publish.
+        if std::thread::panicking() {
+            self.0.pending.take();
+            self.0.queued.take();
         }
     }
 }
 
 pub struct State<T>(Rc<InnerState<T>>);
 
-impl<T> State<T> {
-    pub fn bind(&self) -> Binding<T> {
-        Binding(self.0.clone())
+impl<T: 'static> State<T> {
+    /// Creates a state whose assignments are skipped when `equals` finds them unchanged,
+    /// e.g. to compare floats within a tolerance.
+    pub fn with_equality(value: T, equals: impl Fn(&T, &T) -> bool + 'static) -> State<T> {
+        State::register(Rc::new_cyclic(|this| InnerState {
+            this: this.clone(),
+            equals: Some(Box::new(equals)),
+            ..value.into()
+        }))
+    }
+
+    fn register(state: Rc<InnerState<T>>) -> State<T> {
+        debug::register(Rc::downgrade(&state) as Weak<dyn debug::Inspect>);
+        State(state)
+    }
+
+    /// Names the state in the [`debug`] graph.
+    pub fn named(self, name: impl Into<String>) -> State<T> {
+        self.set_debug_name(name);
+        self
+    }
+
+    /// Creates a read-only binding to the state, for views that only display it.
+    pub fn bind(&self) -> ReadBinding<T> {
+        ReadBinding(self.0.clone())
+    }
+
+    /// Creates a binding through which the state can also be assigned.
+    pub fn bind_mut(&self) -> WriteBinding<T> {
+        WriteBinding::new(self.0.clone())
     }
 }
 
@@ -124,18 +473,177 @@ impl<T> std::ops::Deref for State<T> {
     }
 }
 
-impl<T> From<T> for State<T> {
+impl<T: 'static> From<T> for State<T> {
     fn from(value: T) -> Self {
-        State(Rc::new(value.into()))
+        State::register(Rc::new_cyclic(|this| InnerState {
+            this: this.clone(),
+            ..value.into()
+        }))
     }
 }
 
-pub struct Binding<T>(Rc<InnerState<T>>);
+/// A read-only handle to a state owned by another view, or to a field of one.
+///
+/// The value can be read and subscribed to, but not assigned.
+pub struct ReadBinding<T>(Rc<dyn Source<T>>);
 
-impl<T> std::ops::Deref for Binding<T> {
-    type Target = InnerState<T>;
+impl<T: 'static> ReadBinding<T> {
+    /// Registers `f` to be called every time the state is published.
+    pub fn subscribe(&self, f: impl FnMut(&T) + 'static) -> Subscription {
+        self.0.subscribe(Box::new(f))
+    }
+
+    /// Immutably borrows the current value.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.0.borrow()
+    }
+
+    /// Calls `f` with a reference to the current value.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.borrow())
+    }
+
+    /// Focuses the binding on the part of the value returned by `get`.
+    ///
+    /// Subscribers of the returned binding are notified every time the whole state is.
+    pub fn map_field<U: 'static>(&self, get: impl Fn(&T) -> &U + 'static) -> ReadBinding<U> {
+        ReadBinding(Rc::new(ReadLens {
+            parent: self.0.clone(),
+            get: Rc::new(get),
+        }))
+    }
+}
+
+impl<T: Copy + 'static> ReadBinding<T> {
+    pub fn value(&self) -> T {
+        *self.borrow()
+    }
+}
+
+impl<T: Clone + 'static> ReadBinding<T> {
+    /// Returns a clone of the current value.
+    pub fn cloned(&self) -> T {
+        self.borrow().clone()
+    }
+}
+
+impl<T> Clone for ReadBinding<T> {
+    fn clone(&self) -> Self {
+        ReadBinding(self.0.clone())
+    }
+}
+
+/// A handle to a state owned by another view, or to a field of one, through which the
+/// value can be assigned.
+pub struct WriteBinding<T> {
+    sink: Rc<dyn Sink<T>>,
+    read: ReadBinding<T>,
+}
+
+impl<T: 'static> WriteBinding<T> {
+    fn new(sink: Rc<dyn Sink<T>>) -> WriteBinding<T> {
+        WriteBinding {
+            read: ReadBinding(sink.clone()),
+            sink,
+        }
+    }
+
+    pub fn value_mut(&self) -> StateRefMut<'_, T> {
+        StateRefMut(&*self.sink)
+    }
+
+    /// Mutates the value in place and publishes the result to every subscriber.
+    pub fn update(&self, f: impl FnOnce(&mut T) + 'static) {
+        self.sink.modify(Box::new(f));
+    }
+}
+
+impl<T: Clone + 'static> WriteBinding<T> {
+    /// Focuses the binding on the part of the value returned by `get` and `get_mut`.
+    ///
+    /// Writes through the returned binding update the whole state, notifying the subscribers
+    /// of both the state and the field. A write made while the state is being published
+    /// goes to a copy of it, published once its subscribers have seen the current value.
+    ///
+    /// ```ignore
+    /// let name: WriteBinding<String> = form.bind_mut().map_field(|f| &f.name, |f| &mut f.name);
+    /// ```
+    pub fn map_field<U: 'static>(
+        &self,
+        get: impl Fn(&T) -> &U + 'static,
+        get_mut: impl Fn(&mut T) -> &mut U + 'static,
+    ) -> WriteBinding<U> {
+        WriteBinding::new(Rc::new(WriteLens {
+            parent: self.sink.clone(),
+            get: Rc::new(get),
+            get_mut: Rc::new(get_mut),
+        }))
+    }
+}
+
+impl<T> WriteBinding<T> {
+    /// Gives up write access to the state.
+    pub fn read_only(&self) -> ReadBinding<T> {
+        self.read.clone()
+    }
+}
+
+impl<T> Clone for WriteBinding<T> {
+    fn clone(&self) -> Self {
+        WriteBinding {
+            sink: self.sink.clone(),
+            read: self.read.clone(),
+        }
+    }
+}
+
+impl<T> std::ops::Deref for WriteBinding<T> {
+    type Target = ReadBinding<T>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.read
     }
-}
\ No newline at end of file
+}
+
+/// Conversion from a state, memo or binding into the binding a child view asks for.
+///
+/// The `view!` macro uses it to pass `$state` arguments, so a child that declares a
+/// [`WriteBinding`] parameter can only be given something that may be written.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be passed as a `{B}`",
+    label = "this source is read-only",
+    note = "a child view can only take a `WriteBinding` to a `State` or another `WriteBinding`"
+)]
+pub trait ToBinding<B> {
+    fn to_binding(&self) -> B;
+}
+
+impl<T: 'static> ToBinding<ReadBinding<T>> for State<T> {
+    fn to_binding(&self) -> ReadBinding<T> {
+        self.bind()
+    }
+}
+
+impl<T: 'static> ToBinding<WriteBinding<T>> for State<T> {
+    fn to_binding(&self) -> WriteBinding<T> {
+        self.bind_mut()
+    }
+}
+
+impl<T> ToBinding<ReadBinding<T>> for ReadBinding<T> {
+    fn to_binding(&self) -> ReadBinding<T> {
+        self.clone()
+    }
+}
+
+impl<T> ToBinding<ReadBinding<T>> for WriteBinding<T> {
+    fn to_binding(&self) -> ReadBinding<T> {
+        self.read_only()
+    }
+}
+
+impl<T> ToBinding<WriteBinding<T>> for WriteBinding<T> {
+    fn to_binding(&self) -> WriteBinding<T> {
+        self.clone()
+    }
+}
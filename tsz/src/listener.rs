@@ -0,0 +1,97 @@
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{AddEventListenerOptions, Event, EventTarget};
+
+/// How an [`EventListener`] is added, see `addEventListener`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ListenerOptions {
+    /// Run the listener while the event travels down to its target, before the target's
+    /// own listeners.
+    pub capture: bool,
+    /// Remove the listener after it ran once.
+    pub once: bool,
+    /// Promise not to call `prevent_default`, which lets the browser scroll without waiting
+    /// for the listener.
+    pub passive: bool,
+}
+
+/// An event listener that is removed from its target when dropped.
+///
+/// Listeners added by views are owned by their [`Scope`](crate::Scope), so the closure and
+/// everything it captures is released when the view is torn down.
+pub struct EventListener {
+    target: EventTarget,
+    event: String,
+    capture: bool,
+    closure: Closure<dyn FnMut(Event)>,
+}
+
+impl EventListener {
+    pub fn new(
+        target: &EventTarget,
+        event: &str,
+        f: impl FnMut(Event) + 'static,
+    ) -> Result<EventListener, JsValue> {
+        EventListener::with_options(target, event, ListenerOptions::default(), f)
+    }
+
+    pub fn with_options(
+        target: &EventTarget,
+        event: &str,
+        options: ListenerOptions,
+        f: impl FnMut(Event) + 'static,
+    ) -> Result<EventListener, JsValue> {
+        let closure: Closure<dyn FnMut(Event)> = Closure::new(f);
+        target.add_event_listener_with_callback_and_add_event_listener_options(
+            event,
+            closure.as_ref().unchecked_ref(),
+            AddEventListenerOptions::new()
+                .capture(options.capture)
+                .once(options.once)
+                .passive(options.passive),
+        )?;
+
+        Ok(EventListener {
+            target: target.clone(),
+            event: event.to_string(),
+            capture: options.capture,
+            closure,
+        })
+    }
+}
+
+impl Drop for EventListener {
+    fn drop(&mut self) {
+        // A listener is only removed if `capture` matches the one it was added with.
+        let _ = self.target.remove_event_listener_with_callback_and_bool(
+            &self.event,
+            self.closure.as_ref().unchecked_ref(),
+            self.capture,
+        );
+    }
+}
+
+/// A view method that handles an event, bound with `click: @on_click` in `view!`.
+///
+/// The method may take the event as well, typed after the event it handles:
+///
+/// ```ignore
+/// fn on_click(&self) { ... }
+/// fn on_key_down(&self, event: KeyboardEvent) { ... }
+/// ```
+///
+/// `Args` only tells the two kinds of methods apart and is inferred.
+pub trait EventHandler<V, E, Args> {
+    fn call(&self, view: &V, event: E);
+}
+
+impl<V, E, F: Fn(&V)> EventHandler<V, E, ()> for F {
+    fn call(&self, view: &V, _event: E) {
+        self(view)
+    }
+}
+
+impl<V, E, F: Fn(&V, E)> EventHandler<V, E, (E,)> for F {
+    fn call(&self, view: &V, event: E) {
+        self(view, event)
+    }
+}
@@ -0,0 +1,92 @@
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use crate::{
+    batch::{Job, JobId},
+    tracking::{self, Dependencies},
+    ReadBinding, State, ToBinding,
+};
+
+struct MemoInner<T> {
+    id: JobId,
+    this: Weak<MemoInner<T>>,
+    f: Box<dyn Fn() -> T>,
+    state: State<T>,
+    read: ReadBinding<T>,
+    dependencies: RefCell<Dependencies>,
+}
+
+impl<T: PartialEq + 'static> MemoInner<T> {
+    fn evaluate(&self) {
+        let previous = self.dependencies.take();
+        let (value, dependencies) = tracking::track(self.id, self.this.clone(), previous, &self.f);
+        self.dependencies.replace(dependencies);
+
+        self.state.value_mut().assign(value);
+    }
+}
+
+impl<T: PartialEq + 'static> Job for MemoInner<T> {
+    fn run(&self) {
+        self.evaluate();
+    }
+}
+
+/// A value derived from other states.
+///
+/// Every state read while `f` runs becomes a dependency of the memo, and the value is
+/// recomputed whenever one of them publishes. Subscribers are only notified when the
+/// recomputed value differs from the previous one. The memo stops updating once it is dropped.
+///
+/// ```ignore
+/// let count: State<u64> = 1.into();
+/// let doubled = Memo::new({
+///     let count = count.bind();
+///     move || count.value() * 2
+/// });
+/// ```
+pub struct Memo<T>(Rc<MemoInner<T>>);
+
+impl<T: PartialEq + 'static> Memo<T> {
+    pub fn new(f: impl Fn() -> T + 'static) -> Memo<T> {
+        let id = JobId::new();
+        Memo(Rc::new_cyclic(|this: &Weak<MemoInner<T>>| {
+            let (value, dependencies) =
+                tracking::track(id, this.clone(), Dependencies::default(), &f);
+
+            let state: State<T> = value.into();
+
+            MemoInner {
+                id,
+                this: this.clone(),
+                f: Box::new(f),
+                read: state.bind(),
+                state,
+                dependencies: RefCell::new(dependencies),
+            }
+        }))
+    }
+}
+
+impl<T> Memo<T> {
+    /// Creates a read-only binding to the memo's value.
+    pub fn bind(&self) -> ReadBinding<T> {
+        self.0.read.clone()
+    }
+}
+
+impl<T> std::ops::Deref for Memo<T> {
+    type Target = ReadBinding<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0.read
+    }
+}
+
+impl<T> ToBinding<ReadBinding<T>> for Memo<T> {
+    fn to_binding(&self) -> ReadBinding<T> {
+        self.bind()
+    }
+}
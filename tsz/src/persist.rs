@@ -0,0 +1,191 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+
+use crate::{State, Subscription};
+
+/// Somewhere to keep persisted values between runs of the app.
+pub trait Storage {
+    fn get(&self, key: &str) -> Option<String>;
+
+    fn set(&self, key: &str, value: &str);
+
+    fn remove(&self, key: &str);
+}
+
+/// The browser's `localStorage`.
+///
+/// Reads return `None` and writes are dropped when local storage is unavailable, e.g.
+/// because the user disabled it.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LocalStorage;
+
+impl LocalStorage {
+    fn storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+}
+
+impl Storage for LocalStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        LocalStorage::storage()?.get_item(key).ok()?
+    }
+
+    fn set(&self, key: &str, value: &str) {
+        if let Some(storage) = LocalStorage::storage() {
+            let _ = storage.set_item(key, value);
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        if let Some(storage) = LocalStorage::storage() {
+            let _ = storage.remove_item(key);
+        }
+    }
+}
+
+/// A storage that only lives as long as the program, for tests and native builds.
+///
+/// Clones share the same entries.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryStorage(Rc<RefCell<HashMap<String, String>>>);
+
+impl MemoryStorage {
+    pub fn new() -> MemoryStorage {
+        MemoryStorage::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        self.0.borrow().get(key).cloned()
+    }
+
+    fn set(&self, key: &str, value: &str) {
+        self.0
+            .borrow_mut()
+            .insert(key.to_string(), value.to_string());
+    }
+
+    fn remove(&self, key: &str) {
+        self.0.borrow_mut().remove(key);
+    }
+}
+
+type Migration = Box<dyn Fn(Value) -> Value>;
+
+/// The version of a persisted type, and how to upgrade values stored by older versions.
+///
+/// ```ignore
+/// // Version 1 renamed `name` to `title`.
+/// let schema = Schema::new(1).migration(0, |mut value| {
+///     value["title"] = value["name"].take();
+///     value
+/// });
+/// ```
+pub struct Schema {
+    version: u32,
+    migrations: HashMap<u32, Migration>,
+}
+
+impl Schema {
+    pub fn new(version: u32) -> Schema {
+        Schema {
+            version,
+            migrations: HashMap::new(),
+        }
+    }
+
+    /// Registers how to turn a value stored by version `from` into one for version `from + 1`.
+    pub fn migration(mut self, from: u32, migrate: impl Fn(Value) -> Value + 'static) -> Schema {
+        self.migrations.insert(from, Box::new(migrate));
+        self
+    }
+
+    /// Brings a value stored by `version` up to date, if every step in between is known.
+    fn migrate(&self, mut version: u32, mut value: Value) -> Option<Value> {
+        while version < self.version {
+            value = self.migrations.get(&version)?(value);
+            version += 1;
+        }
+
+        (version == self.version).then_some(value)
+    }
+}
+
+impl Default for Schema {
+    fn default() -> Self {
+        Schema::new(0)
+    }
+}
+
+/// A state that is saved to a [`Storage`] every time it changes, and restored from it when
+/// it is created.
+///
+/// Values are stored as JSON together with the version of their [`Schema`]. A stored value
+/// that can't be migrated to the current version or deserialized is ignored and the default
+/// is used instead.
+///
+/// ```ignore
+/// let theme = Persisted::new("theme", Theme::Light, LocalStorage);
+/// theme.value_mut().assign(Theme::Dark);
+/// ```
+pub struct Persisted<T> {
+    state: State<T>,
+    _subscription: Subscription,
+}
+
+impl<T: Serialize + DeserializeOwned + 'static> Persisted<T> {
+    pub fn new(
+        key: impl Into<String>,
+        default: T,
+        storage: impl Storage + 'static,
+    ) -> Persisted<T> {
+        Persisted::with_schema(key, default, storage, Schema::default())
+    }
+
+    pub fn with_schema(
+        key: impl Into<String>,
+        default: T,
+        storage: impl Storage + 'static,
+        schema: Schema,
+    ) -> Persisted<T> {
+        let key = key.into();
+
+        let value = storage
+            .get(&key)
+            .and_then(|stored| restore(&schema, &stored))
+            .unwrap_or(default);
+        let state: State<T> = value.into();
+
+        let version = schema.version;
+        let subscription = state.subscribe(move |value| {
+            if let Ok(value) = serde_json::to_value(value) {
+                let stored = json!({ "version": version, "value": value });
+                storage.set(&key, &stored.to_string());
+            }
+        });
+
+        Persisted {
+            state,
+            _subscription: subscription,
+        }
+    }
+}
+
+fn restore<T: DeserializeOwned>(schema: &Schema, stored: &str) -> Option<T> {
+    let mut stored: Value = serde_json::from_str(stored).ok()?;
+    let version = u32::try_from(stored.get("version")?.as_u64()?).ok()?;
+    let value = schema.migrate(version, stored.get_mut("value")?.take())?;
+
+    serde_json::from_value(value).ok()
+}
+
+impl<T> std::ops::Deref for Persisted<T> {
+    type Target = State<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state
+    }
+}
@@ -0,0 +1,250 @@
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use crate::{effect, task, untrack, Effect, Memo, ReadBinding, State};
+
+/// The value of a [`Resource`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResourceState<T, E> {
+    Loading,
+    Ready(T),
+    Error(E),
+}
+
+impl<T, E> ResourceState<T, E> {
+    pub fn is_loading(&self) -> bool {
+        matches!(self, ResourceState::Loading)
+    }
+
+    pub fn is_ready(&self) -> bool {
+        matches!(self, ResourceState::Ready(_))
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self, ResourceState::Error(_))
+    }
+
+    pub fn ready(&self) -> Option<&T> {
+        match self {
+            ResourceState::Ready(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn error(&self) -> Option<&E> {
+        match self {
+            ResourceState::Error(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Displays the loaded value or the error, and nothing while loading.
+impl<T: fmt::Display, E: fmt::Display> fmt::Display for ResourceState<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResourceState::Loading => Ok(()),
+            ResourceState::Ready(value) => value.fmt(f),
+            ResourceState::Error(error) => error.fmt(f),
+        }
+    }
+}
+
+type Request<T, E> = Pin<Box<dyn Future<Output = Result<T, E>>>>;
+
+type Fetcher<T, E> = Rc<dyn Fn() -> Request<T, E>>;
+
+/// Cancels a request, waking its task so the request's future is dropped right away.
+#[derive(Default)]
+struct Cancel {
+    cancelled: Cell<bool>,
+    waker: RefCell<Option<Waker>>,
+}
+
+impl Cancel {
+    fn cancel(&self) {
+        self.cancelled.set(true);
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Resolves to `None` without polling the inner future again once it is cancelled.
+struct Cancellable<F: ?Sized> {
+    future: Pin<Box<F>>,
+    cancel: Rc<Cancel>,
+}
+
+impl<F: Future + ?Sized> Future for Cancellable<F> {
+    type Output = Option<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.cancel.cancelled.get() {
+            return Poll::Ready(None);
+        }
+
+        self.cancel.waker.replace(Some(cx.waker().clone()));
+        self.future.as_mut().poll(cx).map(Some)
+    }
+}
+
+struct Fetch<T, E> {
+    state: State<ResourceState<T, E>>,
+    /// Cancels the request that is currently in flight.
+    pending: RefCell<Option<Rc<Cancel>>>,
+}
+
+impl<T: 'static, E: 'static> Fetch<T, E> {
+    fn start(self: &Rc<Self>, future: Request<T, E>) {
+        self.cancel_pending();
+
+        let cancel = Rc::new(Cancel::default());
+        self.pending.replace(Some(cancel.clone()));
+
+        if !self.state.borrow().is_loading() {
+            self.state.value_mut().set_force(ResourceState::Loading);
+        }
+
+        let fetch = Rc::downgrade(self);
+        task::spawn_local(async move {
+            let Some(result) = (Cancellable { future, cancel }).await else {
+                return;
+            };
+
+            if let Some(fetch) = fetch.upgrade() {
+                fetch.pending.take();
+                fetch.state.value_mut().set_force(match result {
+                    Ok(value) => ResourceState::Ready(value),
+                    Err(error) => ResourceState::Error(error),
+                });
+            }
+        });
+    }
+}
+
+impl<T, E> Fetch<T, E> {
+    fn cancel_pending(&self) {
+        if let Some(cancel) = self.pending.take() {
+            cancel.cancel();
+        }
+    }
+}
+
+impl<T, E> Drop for Fetch<T, E> {
+    fn drop(&mut self) {
+        self.cancel_pending();
+    }
+}
+
+/// A value loaded asynchronously into state.
+///
+/// The fetcher is called right away and again whenever one of the states it reads before
+/// returning its future changes. A request that is still in flight when a new one starts,
+/// or when the resource is dropped, is cancelled and its result discarded.
+///
+/// The resource derefs to a binding of its [`ResourceState`], which displays the loaded value
+/// in text, and [`Resource::loading`], [`Resource::ready`] and [`Resource::failed`] can be
+/// passed to `If`.
+///
+/// ```ignore
+/// let user = Resource::new({
+///     let id = id.bind();
+///     move || {
+///         let id = id.value();
+///         async move { fetch_user(id).await }
+///     }
+/// });
+/// ```
+pub struct Resource<T, E> {
+    fetch: Rc<Fetch<T, E>>,
+    fetcher: Fetcher<T, E>,
+    state: ReadBinding<ResourceState<T, E>>,
+    loading: Memo<bool>,
+    ready: Memo<bool>,
+    failed: Memo<bool>,
+    _effect: Effect,
+}
+
+impl<T: 'static, E: 'static> Resource<T, E> {
+    pub fn new<F>(fetcher: impl Fn() -> F + 'static) -> Resource<T, E>
+    where
+        F: Future<Output = Result<T, E>> + 'static,
+    {
+        let fetcher: Fetcher<T, E> = Rc::new(move || {
+            let future: Request<T, E> = Box::pin(fetcher());
+            future
+        });
+        let state: State<ResourceState<T, E>> = ResourceState::Loading.into();
+
+        let flag = |is: fn(&ResourceState<T, E>) -> bool| {
+            let state = state.bind();
+            Memo::new(move || state.with(is))
+        };
+        let (loading, ready, failed) = (
+            flag(ResourceState::is_loading),
+            flag(ResourceState::is_ready),
+            flag(ResourceState::is_error),
+        );
+
+        let fetch = Rc::new(Fetch {
+            state,
+            pending: RefCell::new(None),
+        });
+
+        let effect = effect({
+            let fetch = fetch.clone();
+            let fetcher = fetcher.clone();
+            move || {
+                // Only the states read while creating the future are sources of the resource.
+                let future = fetcher();
+                untrack(|| fetch.start(future));
+            }
+        });
+
+        Resource {
+            state: fetch.state.bind(),
+            fetch,
+            fetcher,
+            loading,
+            ready,
+            failed,
+            _effect: effect,
+        }
+    }
+
+    /// Fetches the value again, cancelling the request in flight if there is one.
+    pub fn refetch(&self) {
+        let future = untrack(|| (self.fetcher)());
+        self.fetch.start(future);
+    }
+
+    /// Whether a request is in flight.
+    pub fn loading(&self) -> ReadBinding<bool> {
+        self.loading.bind()
+    }
+
+    /// Whether the last request succeeded.
+    pub fn ready(&self) -> ReadBinding<bool> {
+        self.ready.bind()
+    }
+
+    /// Whether the last request failed.
+    pub fn failed(&self) -> ReadBinding<bool> {
+        self.failed.bind()
+    }
+}
+
+impl<T, E> std::ops::Deref for Resource<T, E> {
+    type Target = ReadBinding<ResourceState<T, E>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state
+    }
+}
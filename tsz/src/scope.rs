@@ -0,0 +1,123 @@
+use std::{any::Any, cell::RefCell, rc::Rc};
+
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Event, EventTarget};
+
+use crate::{
+    context::{self, Contexts},
+    effect, Effect, EventListener, ListenerOptions, Subscription,
+};
+
+/// Owns the reactive work created while initializing a view.
+///
+/// Everything owned by the scope is released when it is disposed or dropped, so tearing
+/// down a view unsubscribes all of the callbacks, effects and event listeners that its
+/// `on_init` registered, and disposes the scopes of the views nested inside.
+///
+/// Scopes form a tree that mirrors the views, which is also how contexts provided with
+/// [`provide_context`](crate::provide_context) reach the views nested inside.
+#[derive(Default)]
+pub struct Scope {
+    subscriptions: RefCell<Vec<Subscription>>,
+    effects: RefCell<Vec<Effect>>,
+    listeners: RefCell<Vec<EventListener>>,
+    children: RefCell<Vec<Scope>>,
+    /// The view instances themselves, which their callbacks only reference weakly.
+    views: RefCell<Vec<Rc<dyn Any>>>,
+    contexts: Rc<Contexts>,
+}
+
+impl Scope {
+    pub fn new() -> Scope {
+        Scope::default()
+    }
+
+    /// Creates a scope for a nested view, which sees the contexts provided by this one.
+    pub fn child(&self) -> Scope {
+        Scope {
+            contexts: Contexts::child(&self.contexts),
+            ..Scope::default()
+        }
+    }
+
+    /// Runs `f` with this scope as the target of `provide_context` and `use_context`.
+    pub fn enter<R>(&self, f: impl FnOnce() -> R) -> R {
+        context::enter(self.contexts.clone(), f)
+    }
+
+    /// Keeps the scope of a nested view alive until this scope is disposed.
+    pub fn own_scope(&self, scope: Scope) {
+        self.children.borrow_mut().push(scope);
+    }
+
+    /// Keeps the view created in this scope alive until the scope is disposed.
+    pub fn own_view(&self, view: Rc<dyn Any>) {
+        self.views.borrow_mut().push(view);
+    }
+
+    /// Keeps `subscription` alive until this scope is disposed.
+    pub fn own(&self, subscription: Subscription) {
+        self.subscriptions.borrow_mut().push(subscription);
+    }
+
+    /// Keeps `effect` running until this scope is disposed.
+    pub fn own_effect(&self, effect: Effect) {
+        self.effects.borrow_mut().push(effect);
+    }
+
+    /// Keeps `listener` attached until this scope is disposed.
+    pub fn own_listener(&self, listener: EventListener) {
+        self.listeners.borrow_mut().push(listener);
+    }
+
+    /// Listens to `event` on `target` until this scope is disposed.
+    ///
+    /// The event is passed to `f` as `E`, e.g. `KeyboardEvent` for `keydown`, without
+    /// checking that it actually is one.
+    pub fn listen<E: JsCast>(
+        &self,
+        target: &EventTarget,
+        event: &str,
+        f: impl FnMut(E) + 'static,
+    ) -> Result<(), JsValue> {
+        self.listen_with(target, event, ListenerOptions::default(), f)
+    }
+
+    /// Like [`Scope::listen`], adding the listener with `options`.
+    pub fn listen_with<E: JsCast>(
+        &self,
+        target: &EventTarget,
+        event: &str,
+        options: ListenerOptions,
+        mut f: impl FnMut(E) + 'static,
+    ) -> Result<(), JsValue> {
+        let listener = EventListener::with_options(target, event, options, move |event: Event| {
+            f(event.unchecked_into())
+        })?;
+        self.own_listener(listener);
+        Ok(())
+    }
+
+    /// Creates an [`effect`] that is disposed together with this scope.
+    pub fn effect(&self, f: impl FnMut() + 'static) {
+        self.own_effect(effect(f));
+    }
+
+    /// Releases everything owned by this scope. The scope may be reused afterwards.
+    pub fn dispose(&self) {
+        let children = self.children.take();
+        drop(children);
+
+        let listeners = self.listeners.take();
+        drop(listeners);
+
+        let effects = self.effects.take();
+        drop(effects);
+
+        let subscriptions = self.subscriptions.take();
+        drop(subscriptions);
+
+        let views = self.views.take();
+        drop(views);
+    }
+}
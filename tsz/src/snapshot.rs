@@ -0,0 +1,100 @@
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::{batch, ReadBinding, State, WriteBinding};
+
+/// Assigns a deserialized snapshot, see [`Snapshot::prepare_restore`].
+pub type Restore<'a> = Box<dyn FnOnce() + 'a>;
+
+/// Saving and restoring the current values of a view's state.
+///
+/// Implemented for [`State`] and the bindings, and derived for a view with
+/// `#[derive(Snapshot)]`, which snapshots every field into a JSON object keyed by field
+/// name. Fields that hold no state, such as memos or resources, are left out with
+/// `#[snapshot(skip)]`.
+///
+/// ```ignore
+/// #[derive(Snapshot)]
+/// pub struct MyView {
+///     value: State<bool>,
+///     #[snapshot(skip)]
+///     doubled: Memo<u64>,
+/// }
+///
+/// let dump = serde_json::to_string(&view.snapshot()?)?;
+/// view.restore(serde_json::from_str(&dump)?)?;
+/// ```
+pub trait Snapshot {
+    fn snapshot(&self) -> Result<Value, serde_json::Error>;
+
+    /// Deserializes `snapshot` without assigning anything yet, returning the assignment to
+    /// make once every other part of a larger snapshot deserialized too.
+    fn prepare_restore(&self, snapshot: Value) -> Result<Restore<'_>, serde_json::Error>;
+
+    /// Assigns the values in `snapshot`, publishing them to subscribers at once.
+    ///
+    /// Nothing is assigned if any part of the snapshot fails to deserialize.
+    fn restore(&self, snapshot: Value) -> Result<(), serde_json::Error> {
+        let restore = self.prepare_restore(snapshot)?;
+        batch(restore);
+
+        Ok(())
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + 'static> Snapshot for State<T> {
+    fn snapshot(&self) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(&*self.borrow())
+    }
+
+    fn prepare_restore(&self, snapshot: Value) -> Result<Restore<'_>, serde_json::Error> {
+        let value: T = serde_json::from_value(snapshot)?;
+        Ok(Box::new(move || self.value_mut().set_force(value)))
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + 'static> Snapshot for WriteBinding<T> {
+    fn snapshot(&self) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(&*self.borrow())
+    }
+
+    fn prepare_restore(&self, snapshot: Value) -> Result<Restore<'_>, serde_json::Error> {
+        let value: T = serde_json::from_value(snapshot)?;
+        Ok(Box::new(move || self.value_mut().set_force(value)))
+    }
+}
+
+/// A read-only binding is included in snapshots, but restored by the view that owns the state.
+impl<T: Serialize + 'static> Snapshot for ReadBinding<T> {
+    fn snapshot(&self) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(&*self.borrow())
+    }
+
+    fn prepare_restore(&self, _snapshot: Value) -> Result<Restore<'_>, serde_json::Error> {
+        Ok(Box::new(|| {}))
+    }
+}
+
+impl<T: Serialize + 'static> Serialize for State<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.borrow().serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de> + 'static> Deserialize<'de> for State<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(State::from)
+    }
+}
+
+impl<T: Serialize + 'static> Serialize for ReadBinding<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.borrow().serialize(serializer)
+    }
+}
+
+impl<T: Serialize + 'static> Serialize for WriteBinding<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.borrow().serialize(serializer)
+    }
+}
@@ -0,0 +1,138 @@
+use std::{
+    cell::{Cell, Ref, RefMut},
+    rc::Rc,
+};
+
+use crate::{Equality, InnerState, Subscription};
+
+/// Something a [`ReadBinding`](crate::ReadBinding) can read from: a state, or a lens onto
+/// part of one.
+pub(crate) trait Source<T> {
+    /// Borrows the value, recording the read in the innermost tracking frame.
+    fn borrow(&self) -> Ref<'_, T>;
+
+    fn subscribe(&self, f: Box<dyn FnMut(&T)>) -> Subscription;
+}
+
+/// Something a [`WriteBinding`](crate::WriteBinding) can also write to.
+pub(crate) trait Sink<T>: Source<T> {
+    /// Borrows the value the source will hold once pending writes are applied, without
+    /// tracking the read.
+    ///
+    /// Returns `None` while in-place updates made by a subscriber are still queued.
+    fn latest(&self) -> Option<Ref<'_, T>>;
+
+    /// While the subscribers hold the value, makes a pending copy of it with the queued
+    /// in-place updates applied, so the latest value can be read and updated right away.
+    fn settle(&self)
+    where
+        T: Clone;
+
+    fn equality(&self) -> Option<&Equality<T>>;
+
+    /// Replaces the value and publishes it.
+    fn set(&self, value: T);
+
+    /// Mutates the value in place and publishes it.
+    fn modify(&self, f: Box<dyn FnOnce(&mut T)>);
+
+    /// Whether [`Sink::modify`] applies the update right away, rather than queueing it until
+    /// the subscribers are done with the value they hold.
+    fn modifies_now(&self) -> bool;
+
+    /// Replaces the value and publishes it, returning the previous one.
+    ///
+    /// Gives `value` back if the previous value is still held by the subscribers.
+    fn swap(&self, value: T) -> Result<T, T>
+    where
+        T: 'static,
+    {
+        if !self.modifies_now() {
+            return Err(value);
+        }
+
+        let previous = Rc::new(Cell::new(None));
+        self.modify(Box::new({
+            let previous = previous.clone();
+            move |current| previous.set(Some(std::mem::replace(current, value)))
+        }));
+
+        Ok(previous
+            .take()
+            .expect("An update applied right away was queued"))
+    }
+}
+
+impl<T: 'static> Source<T> for InnerState<T> {
+    fn borrow(&self) -> Ref<'_, T> {
+        InnerState::borrow(self)
+    }
+
+    fn subscribe(&self, f: Box<dyn FnMut(&T)>) -> Subscription {
+        InnerState::subscribe(self, f)
+    }
+}
+
+impl<T: 'static> Sink<T> for InnerState<T> {
+    fn latest(&self) -> Option<Ref<'_, T>> {
+        if !self.queued.borrow().is_empty() {
+            return None;
+        }
+
+        match Ref::filter_map(self.pending.borrow(), Option::as_ref) {
+            Ok(pending) => Some(pending),
+            Err(_) => Some(self.value.borrow()),
+        }
+    }
+
+    fn settle(&self)
+    where
+        T: Clone,
+    {
+        if !self.dispatching.get() || self.pending.borrow().is_some() {
+            return;
+        }
+
+        // Updates are only queued while nothing is pending, on top of the dispatched value.
+        let mut value = self.value.borrow().clone();
+        for f in self.queued.take() {
+            f(&mut value);
+        }
+        self.pending.replace(Some(value));
+    }
+
+    fn equality(&self) -> Option<&Equality<T>> {
+        self.equals.as_ref()
+    }
+
+    fn set(&self, value: T) {
+        // Subscribers still hold the current value, so the new one is published after them.
+        if self.dispatching.get() {
+            self.queued.borrow_mut().clear();
+            self.pending.replace(Some(value));
+            return;
+        }
+
+        *self.value.borrow_mut() = value;
+
+        self.publish();
+    }
+
+    fn modify(&self, f: Box<dyn FnOnce(&mut T)>) {
+        if self.dispatching.get() {
+            match RefMut::filter_map(self.pending.borrow_mut(), Option::as_mut) {
+                Ok(mut pending) => f(&mut pending),
+                Err(_) => self.queued.borrow_mut().push(f),
+            }
+            return;
+        }
+
+        f(&mut self.value.borrow_mut());
+
+        self.publish();
+    }
+
+    fn modifies_now(&self) -> bool {
+        !self.dispatching.get() || self.pending.borrow().is_some()
+    }
+}
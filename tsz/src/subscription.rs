@@ -0,0 +1,145 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+};
+
+use slab::Slab;
+
+use crate::debug::{self, Origin};
+
+pub(crate) type Callback<T> = Rc<RefCell<Box<dyn FnMut(&T)>>>;
+
+/// A subscribed callback, with where it was subscribed from.
+struct Entry<T> {
+    seq: u64,
+    origin: Option<Origin>,
+    callback: Callback<T>,
+}
+
+/// The callbacks subscribed to a state.
+///
+/// Every callback is tagged with a sequence number so it can be told apart from a later
+/// subscriber that reuses its slab key, and so callbacks are called in subscription order.
+pub(crate) struct Subscribers<T> {
+    next: Cell<u64>,
+    slab: RefCell<Slab<Entry<T>>>,
+}
+
+impl<T> Subscribers<T> {
+    pub(crate) fn new() -> Subscribers<T> {
+        Subscribers {
+            next: Cell::new(0),
+            slab: RefCell::new(Slab::new()),
+        }
+    }
+
+    pub(crate) fn insert(&self, f: Box<dyn FnMut(&T)>) -> usize {
+        let seq = self.next.get();
+        self.next.set(seq + 1);
+
+        self.slab.borrow_mut().insert(Entry {
+            seq,
+            origin: debug::current_origin(),
+            callback: Rc::new(RefCell::new(f)),
+        })
+    }
+
+    /// Copies out the current callbacks in the order they subscribed, so they can be called
+    /// while the list itself is modified.
+    fn snapshot(&self) -> Vec<(usize, u64, Callback<T>)> {
+        let mut snapshot: Vec<_> = self
+            .slab
+            .borrow()
+            .iter()
+            .map(|(key, entry)| (key, entry.seq, entry.callback.clone()))
+            .collect();
+        snapshot.sort_by_key(|(_, seq, _)| *seq);
+
+        snapshot
+    }
+
+    /// Whether the callback taken with [`Subscribers::snapshot`] is still subscribed.
+    fn contains(&self, key: usize, seq: u64) -> bool {
+        matches!(self.slab.borrow().get(key), Some(entry) if entry.seq == seq)
+    }
+
+    /// Where each callback was subscribed from, in subscription order.
+    pub(crate) fn origins(&self) -> Vec<Option<Origin>> {
+        let slab = self.slab.borrow();
+        let mut entries: Vec<_> = slab.iter().map(|(_, entry)| entry).collect();
+        entries.sort_by_key(|entry| entry.seq);
+
+        entries.into_iter().map(|entry| entry.origin).collect()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.slab.borrow().is_empty()
+    }
+
+    /// Calls the callbacks in the order they subscribed.
+    ///
+    /// Callbacks that unsubscribe before their turn are skipped and callbacks that subscribe
+    /// during the call wait for the next one.
+    pub(crate) fn notify(&self, value: &T) {
+        for (key, seq, callback) in self.snapshot() {
+            if !self.contains(key, seq) {
+                continue;
+            }
+
+            // A callback that is already running has been called with this value.
+            if let Ok(mut callback) = callback.try_borrow_mut() {
+                callback(value);
+            }
+        }
+    }
+}
+
+pub(crate) trait Unsubscribe {
+    fn unsubscribe(&self, key: usize);
+}
+
+impl<T> Unsubscribe for Subscribers<T> {
+    fn unsubscribe(&self, key: usize) {
+        // The subscriber is dropped after the slab is released, since dropping it may
+        // unsubscribe other callbacks from this same state.
+        let removed = self.slab.borrow_mut().try_remove(key);
+        drop(removed);
+    }
+}
+
+/// A handle to a subscriber registered on a state.
+///
+/// Dropping the handle unsubscribes the callback. Use [`Subscription::forget`] to keep
+/// the callback alive for as long as the state itself.
+#[must_use = "dropping a `Subscription` immediately unsubscribes it"]
+pub struct Subscription {
+    source: Option<Weak<dyn Unsubscribe>>,
+    key: usize,
+}
+
+impl Subscription {
+    pub(crate) fn new(source: Weak<dyn Unsubscribe>, key: usize) -> Subscription {
+        Subscription {
+            source: Some(source),
+            key,
+        }
+    }
+
+    /// Detaches the handle, leaving the callback subscribed until the state is dropped.
+    pub fn forget(mut self) {
+        self.source = None;
+    }
+
+    /// Removes the callback from the state.
+    pub fn unsubscribe(self) {
+        drop(self)
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Some(source) = self.source.take().and_then(|source| source.upgrade()) {
+            source.unsubscribe(self.key);
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Running futures on the current thread.
+//!
+//! In the browser, futures are handed to the JavaScript event loop. Everywhere else they
+//! are queued on a small thread-local executor, which is driven by calling
+//! [`run_until_stalled`], e.g. from tests. The executor also holds the timers of the
+//! [`SystemClock`](crate::clock::SystemClock), which only run from [`run_until_stalled`].
+
+/// Runs `future` to completion on the current thread.
+#[cfg(target_arch = "wasm32")]
+pub fn spawn_local(future: impl std::future::Future<Output = ()> + 'static) {
+    wasm_bindgen_futures::spawn_local(future);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use local::{clear_timeout, set_timeout};
+#[cfg(not(target_arch = "wasm32"))]
+pub use local::{run_until_stalled, spawn_local};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod local {
+    use std::{
+        cell::RefCell,
+        collections::{BTreeMap, VecDeque},
+        future::Future,
+        pin::Pin,
+        sync::{Arc, Mutex},
+        task::{Context, Poll, Wake, Waker},
+        time::Instant,
+    };
+
+    use slab::Slab;
+
+    type Task = Pin<Box<dyn Future<Output = ()>>>;
+
+    /// The keys of the tasks that were woken and need to be polled again.
+    type Ready = Arc<Mutex<VecDeque<usize>>>;
+
+    struct TaskWaker {
+        key: usize,
+        ready: Ready,
+    }
+
+    impl Wake for TaskWaker {
+        fn wake(self: Arc<Self>) {
+            self.wake_by_ref();
+        }
+
+        fn wake_by_ref(self: &Arc<Self>) {
+            let mut ready = self.ready.lock().unwrap();
+            if !ready.contains(&self.key) {
+                ready.push_back(self.key);
+            }
+        }
+    }
+
+    /// Orders the timers by deadline, and the ones due at the same time by when they were set.
+    pub(crate) type TimerKey = (Instant, u64);
+
+    #[derive(Default)]
+    struct Executor {
+        tasks: Slab<Option<Task>>,
+        ready: Ready,
+        timers: BTreeMap<TimerKey, Box<dyn FnOnce()>>,
+        next_timer: u64,
+    }
+
+    thread_local! {
+        static EXECUTOR: RefCell<Executor> = RefCell::new(Executor::default());
+    }
+
+    /// Queues `future` on the thread-local executor.
+    ///
+    /// The future isn't polled until [`run_until_stalled`] is called.
+    pub fn spawn_local(future: impl Future<Output = ()> + 'static) {
+        EXECUTOR.with(|executor| {
+            let mut executor = executor.borrow_mut();
+            let key = executor.tasks.insert(Some(Box::pin(future)));
+            executor.ready.lock().unwrap().push_back(key);
+        })
+    }
+
+    /// Calls `f` from [`run_until_stalled`] once `deadline` has passed, unless the timer is
+    /// cleared first.
+    pub(crate) fn set_timeout(deadline: Instant, f: Box<dyn FnOnce()>) -> TimerKey {
+        EXECUTOR.with(|executor| {
+            let mut executor = executor.borrow_mut();
+            let key = (deadline, executor.next_timer);
+            executor.next_timer += 1;
+            executor.timers.insert(key, f);
+
+            key
+        })
+    }
+
+    /// Removes a timer that hasn't run yet.
+    pub(crate) fn clear_timeout(key: TimerKey) {
+        // The callback is dropped after the executor is released.
+        let removed = EXECUTOR.try_with(|executor| executor.borrow_mut().timers.remove(&key));
+        drop(removed);
+    }
+
+    /// Runs the earliest timer if it is due, returning whether there was one.
+    fn run_due_timer() -> bool {
+        let due = EXECUTOR.with(|executor| {
+            let mut executor = executor.borrow_mut();
+            let entry = executor.timers.first_entry()?;
+            (entry.key().0 <= Instant::now()).then(|| entry.remove())
+        });
+
+        match due {
+            Some(f) => {
+                f();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Polls every task that can make progress, and runs every timer that is due, until all
+    /// of them are finished or waiting.
+    ///
+    /// Returns the number of tasks and timers that are still pending.
+    pub fn run_until_stalled() -> usize {
+        loop {
+            let next = EXECUTOR.with(|executor| {
+                let mut executor = executor.borrow_mut();
+                let key = executor.ready.lock().unwrap().pop_front()?;
+                let task = executor.tasks.get_mut(key).and_then(Option::take);
+
+                Some((key, task, executor.ready.clone()))
+            });
+
+            // Timers only run once no task can make progress, and may wake or spawn more.
+            let Some((key, task, ready)) = next else {
+                if run_due_timer() {
+                    continue;
+                }
+                break;
+            };
+
+            // A task that finished can still be woken, e.g. by a waker it left behind.
+            let Some(mut task) = task else {
+                continue;
+            };
+
+            // The task is polled without the executor borrowed, so it can spawn more tasks.
+            let waker = Waker::from(Arc::new(TaskWaker { key, ready }));
+            let poll = task.as_mut().poll(&mut Context::from_waker(&waker));
+
+            match poll {
+                Poll::Ready(()) => {
+                    EXECUTOR.with(|executor| executor.borrow_mut().tasks.remove(key));
+                }
+                Poll::Pending => {
+                    EXECUTOR.with(|executor| executor.borrow_mut().tasks[key] = Some(task));
+                }
+            }
+        }
+
+        EXECUTOR.with(|executor| {
+            let executor = executor.borrow();
+            executor.tasks.len() + executor.timers.len()
+        })
+    }
+}
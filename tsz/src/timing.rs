@@ -0,0 +1,132 @@
+use std::{
+    cell::{Cell, Ref, RefCell},
+    rc::{Rc, Weak},
+    time::Duration,
+};
+
+use crate::{
+    clock::{self, Clock, Timer},
+    source::Source,
+    ReadBinding, State, Subscription,
+};
+
+/// A state that follows another one on a timer.
+struct Timed<T> {
+    this: Weak<Timed<T>>,
+    state: State<T>,
+    clock: Rc<dyn Clock>,
+    /// The pending timers, with whether they already ran.
+    timers: RefCell<Vec<(Rc<Cell<bool>>, Timer)>>,
+    /// For throttling, the value waiting for the current window to end, if any.
+    latest: RefCell<Option<T>>,
+    throttling: Cell<bool>,
+    _subscription: RefCell<Option<Subscription>>,
+}
+
+impl<T: Clone + 'static> Timed<T> {
+    fn new(
+        source: &ReadBinding<T>,
+        on_change: fn(&Timed<T>, &T, Duration),
+        duration: Duration,
+    ) -> Rc<Timed<T>> {
+        let timed = Rc::new_cyclic(|this| Timed {
+            this: this.clone(),
+            state: source.with(T::clone).into(),
+            clock: clock::clock(),
+            timers: RefCell::new(Vec::new()),
+            latest: RefCell::new(None),
+            throttling: Cell::new(false),
+            _subscription: RefCell::new(None),
+        });
+
+        let this = Rc::downgrade(&timed);
+        let subscription = source.subscribe(move |value| {
+            if let Some(timed) = this.upgrade() {
+                on_change(&timed, value, duration);
+            }
+        });
+        timed._subscription.replace(Some(subscription));
+
+        timed
+    }
+
+    /// Calls `f` on the timed state once `delay` has passed.
+    fn after(&self, delay: Duration, f: impl FnOnce(&Timed<T>) + 'static) {
+        let this = self.this.clone();
+        let done = Rc::new(Cell::new(false));
+        let timer = self.clock.set_timeout(
+            delay,
+            Box::new({
+                let done = done.clone();
+                move || {
+                    done.set(true);
+                    if let Some(timed) = this.upgrade() {
+                        f(&timed);
+                    }
+                }
+            }),
+        );
+
+        let mut timers = self.timers.borrow_mut();
+        timers.retain(|(done, _)| !done.get());
+        timers.push((done, timer));
+    }
+
+    fn delay(&self, value: &T, duration: Duration) {
+        let value = value.clone();
+        self.after(duration, move |timed| {
+            timed.state.value_mut().set_force(value)
+        });
+    }
+
+    fn debounce(&self, value: &T, duration: Duration) {
+        // Only the timer of the latest change is kept.
+        self.timers.take();
+        self.delay(value, duration);
+    }
+
+    fn throttle(&self, value: &T, duration: Duration) {
+        if self.throttling.get() {
+            self.latest.replace(Some(value.clone()));
+            return;
+        }
+
+        self.throttling.set(true);
+        self.state.value_mut().set_force(value.clone());
+        self.after(duration, move |timed| {
+            timed.throttling.set(false);
+            if let Some(latest) = timed.latest.take() {
+                timed.throttle(&latest, duration);
+            }
+        });
+    }
+}
+
+impl<T: 'static> Source<T> for Timed<T> {
+    fn borrow(&self) -> Ref<'_, T> {
+        self.state.borrow()
+    }
+
+    fn subscribe(&self, f: Box<dyn FnMut(&T)>) -> Subscription {
+        self.state.subscribe(f)
+    }
+}
+
+impl<T: Clone + 'static> ReadBinding<T> {
+    /// Follows this binding once it stopped changing for `duration`, e.g. to search
+    /// after the user stopped typing.
+    pub fn debounce(&self, duration: Duration) -> ReadBinding<T> {
+        ReadBinding(Timed::new(self, Timed::debounce, duration))
+    }
+
+    /// Follows this binding at most once every `duration`, publishing the first change right
+    /// away and the latest one at the end of each interval.
+    pub fn throttle(&self, duration: Duration) -> ReadBinding<T> {
+        ReadBinding(Timed::new(self, Timed::throttle, duration))
+    }
+
+    /// Follows every change of this binding, `duration` later.
+    pub fn delay(&self, duration: Duration) -> ReadBinding<T> {
+        ReadBinding(Timed::new(self, Timed::delay, duration))
+    }
+}
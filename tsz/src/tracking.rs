@@ -0,0 +1,121 @@
+use std::{cell::RefCell, rc::Weak};
+
+use crate::{
+    batch::{self, Job, JobId},
+    InnerState, Subscription,
+};
+
+/// The subscriptions an observer holds on the states it depends on, keyed by state id.
+#[derive(Default)]
+pub(crate) struct Dependencies(Vec<(JobId, Subscription)>);
+
+impl Dependencies {
+    fn contains(&self, id: JobId) -> bool {
+        self.0.iter().any(|(dep, _)| *dep == id)
+    }
+
+    fn take(&mut self, id: JobId) -> Option<Subscription> {
+        let index = self.0.iter().position(|(dep, _)| *dep == id)?;
+        Some(self.0.swap_remove(index).1)
+    }
+}
+
+struct Frame {
+    observer: Option<(JobId, Weak<dyn Job>)>,
+    previous: Dependencies,
+    current: Dependencies,
+}
+
+thread_local! {
+    static FRAMES: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs `f`, recording every state read during the call as a dependency of `observer`,
+/// which is queued as job `id`.
+///
+/// The observer is scheduled to run again whenever one of those states publishes.
+/// Subscriptions in `previous` are reused for states that are read again, and the ones that
+/// are no longer needed are released once `f` returns.
+pub(crate) fn track<R>(
+    id: JobId,
+    observer: Weak<dyn Job>,
+    previous: Dependencies,
+    f: impl FnOnce() -> R,
+) -> (R, Dependencies) {
+    let (result, frame) = with_frame(
+        Frame {
+            observer: Some((id, observer)),
+            previous,
+            current: Dependencies::default(),
+        },
+        f,
+    );
+
+    (result, frame.current)
+}
+
+/// Runs `f` without recording any of the states it reads as dependencies of the
+/// surrounding memo.
+pub fn untrack<R>(f: impl FnOnce() -> R) -> R {
+    with_frame(
+        Frame {
+            observer: None,
+            previous: Dependencies::default(),
+            current: Dependencies::default(),
+        },
+        f,
+    )
+    .0
+}
+
+fn with_frame<R>(frame: Frame, f: impl FnOnce() -> R) -> (R, Frame) {
+    /// Drops the frame if `f` panics, so later reads aren't recorded in it and the
+    /// subscriptions it collected are released.
+    struct Pop;
+
+    impl Drop for Pop {
+        fn drop(&mut self) {
+            let frame = FRAMES.try_with(|frames| frames.borrow_mut().pop());
+            drop(frame);
+        }
+    }
+
+    FRAMES.with(|frames| frames.borrow_mut().push(frame));
+    let pop = Pop;
+    let result = f();
+    std::mem::forget(pop);
+
+    let frame = FRAMES
+        .with(|frames| frames.borrow_mut().pop())
+        .expect("Tracking frame was popped early");
+
+    (result, frame)
+}
+
+/// Records a read of `source` in the innermost tracking frame, if there is one.
+pub(crate) fn record<T: 'static>(source: &InnerState<T>) {
+    FRAMES.with(|frames| {
+        let mut frames = frames.borrow_mut();
+        let Some(frame) = frames.last_mut() else {
+            return;
+        };
+        let Some((observer_id, observer)) = &frame.observer else {
+            return;
+        };
+
+        let id = source.id();
+        if frame.current.contains(id) {
+            return;
+        }
+
+        let subscription = match frame.previous.take(id) {
+            Some(subscription) => subscription,
+            None => {
+                let (observer_id, observer) = (*observer_id, observer.clone());
+                source.subscribe(move |_| batch::schedule(observer_id, observer.clone()))
+            }
+        };
+
+        frame.current.0.push((id, subscription));
+    })
+}
@@ -1,7 +1,7 @@
-use crate::{self as tsz, Binding};
+use crate::{self as tsz, ReadBinding};
 
 enum BindingOrIter<T> {
-    Binding(Binding<T>),
+    Binding(ReadBinding<T>),
     Iter(Vec<T>)
 }
 
@@ -1,11 +1,11 @@
-use crate::{self as tsz, Binding};
+use crate::{self as tsz, ReadBinding};
 
 pub struct If {
-    condition: Binding<bool>,
+    condition: ReadBinding<bool>,
 }
 
 impl If {
-    pub fn new(binding: Binding<bool>) -> If {
+    pub fn new(binding: ReadBinding<bool>) -> If {
         If { condition: binding }
     }
 }
@@ -13,40 +13,65 @@ impl If {
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 
+/// Creates the children of an `If` inside the element it is given.
+type Children = fn(&Rc<If>, &tsz::Scope, &Rc<tsz::html::Document>, &tsz::html::Element) -> Result<(), JsValue>;
+
 impl If {
     pub fn on_init(
         self: Rc<Self>,
+        scope: &tsz::Scope,
         document: Rc<tsz::html::Document>,
         parent: &tsz::html::Element,
-        children: Option<
-            fn(&Rc<Self>, &Rc<tsz::html::Document>, &tsz::html::Element) -> Result<tsz::html::Element, JsValue>,
-        >,
+        children: Option<Children>,
     ) -> Result<(), JsValue> {
         let __body = document.body().expect("Unable to get document body");
         let _self = self;
 
-        let children = Rc::new(children.expect("Expected children!"));
-        // let parent = Rc::new(children.expect("Expected children!"));
+        let children = children.expect("Expected children!");
+
+        // The children are placed before this marker, so they keep their position among
+        // their siblings when they are created again.
+        let anchor: web_sys::Node = document.create_text_node("").into();
+        parent.append_child(&anchor)?;
 
-        if _self.condition.value() {
-            // if let Some(view) = &children {
-                children(&_self, &document, &parent)?;
-            // }
-        }
+        // The children get a scope of their own, which releases their subscriptions and
+        // listeners when the condition turns false.
+        let children_scope = scope.child();
+        let mut shown = false;
+        let mut nodes: Vec<web_sys::Node> = Vec::new();
 
         let _selfc = _self.clone();
-        let _children = children.clone();
         let _doc = document.clone();
-        let _parent = parent.clone();
-
-        _self.condition.subscribe(move |value| {
-            let _children = Rc::clone(&_children);
-            if *value {
-                // if let Some(view) = &children {
-                _children(&_selfc, &_doc, &_parent).expect("Creating chidlren failed");
-                // }
+
+        let origin = tsz::debug::Origin { view: std::any::type_name::<Self>(), element: "condition" };
+        tsz::debug::with_origin(origin, || scope.effect(move || {
+            let show = _selfc.condition.value();
+            if show == shown {
+                return;
             }
-        });
+            shown = show;
+
+            // Only the condition is a dependency, not the states read by the children.
+            tsz::untrack(|| {
+                if show {
+                    let container = _doc.create_element("div").expect("Creating children failed");
+                    children(&_selfc, &children_scope, &_doc, &container).expect("Creating children failed");
+
+                    let parent = anchor.parent_node().expect("If was removed from the document");
+                    while let Some(node) = container.first_child() {
+                        parent.insert_before(&node, Some(&anchor)).expect("Creating children failed");
+                        nodes.push(node);
+                    }
+                } else {
+                    children_scope.dispose();
+                    for node in nodes.drain(..) {
+                        if let Some(parent) = node.parent_node() {
+                            let _ = parent.remove_child(&node);
+                        }
+                    }
+                }
+            });
+        }));
 
         Ok(())
     }
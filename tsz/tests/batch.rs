@@ -0,0 +1,139 @@
+use std::{
+    cell::RefCell,
+    panic::{self, AssertUnwindSafe},
+    rc::Rc,
+};
+
+use tsz::{batch, effect, State};
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+#[test]
+fn publishes_each_state_once_with_its_final_value() {
+    let first: State<u32> = 0.into();
+    let second: State<u32> = 0.into();
+    let log = Log::default();
+
+    let _effect = effect({
+        let (first, second, log) = (first.bind(), second.bind(), log.clone());
+        move || {
+            let sum = format!("{} + {}", first.value(), second.value());
+            log.borrow_mut().push(sum);
+        }
+    });
+    let _subscription = first.subscribe({
+        let log = log.clone();
+        move |value| log.borrow_mut().push(format!("first {value}"))
+    });
+
+    batch(|| {
+        first.value_mut().assign(1);
+        second.value_mut().assign(2);
+        first.value_mut().assign(3);
+
+        // Subscribers haven't seen any of it yet.
+        assert_eq!(log.borrow().len(), 1);
+    });
+
+    assert_eq!(*log.borrow(), ["0 + 0", "first 3", "3 + 2"]);
+}
+
+#[test]
+fn nested_batches_publish_when_the_outermost_ends() {
+    let count: State<u32> = 0.into();
+    let log = Log::default();
+    let _subscription = count.subscribe({
+        let log = log.clone();
+        move |value| log.borrow_mut().push(value.to_string())
+    });
+
+    let result = batch(|| {
+        batch(|| count.value_mut().assign(1));
+        assert!(log.borrow().is_empty());
+
+        count.value_mut().assign(2);
+        "done"
+    });
+
+    assert_eq!(result, "done");
+    assert_eq!(*log.borrow(), ["2"]);
+}
+
+#[test]
+fn recovers_after_a_panic() {
+    let count: State<u32> = 0.into();
+    let log = Log::default();
+
+    let panicked = panic::catch_unwind(AssertUnwindSafe(|| {
+        batch(|| {
+            count.value_mut().assign(1);
+            panic!("handler failed");
+        })
+    }));
+    assert!(panicked.is_err());
+
+    // Nothing is left queued, so later publishes reach their subscribers right away.
+    let _subscription = count.subscribe({
+        let log = log.clone();
+        move |value| log.borrow_mut().push(value.to_string())
+    });
+    count.value_mut().assign(2);
+
+    assert_eq!(*log.borrow(), ["2"]);
+}
+
+#[test]
+fn recovers_after_an_update_loop() {
+    let count: State<u32> = 0.into();
+
+    let panicked = panic::catch_unwind(AssertUnwindSafe(|| {
+        let subscription = count.subscribe({
+            let count = count.bind_mut();
+            move |value| count.value_mut().assign(value + 1)
+        });
+        count.value_mut().assign(1);
+        drop(subscription);
+    }));
+    let message = panicked.unwrap_err();
+    assert!(message
+        .downcast_ref::<String>()
+        .unwrap()
+        .starts_with("Update loop detected"));
+
+    let log = Log::default();
+    let _subscription = count.subscribe({
+        let log = log.clone();
+        move |value| log.borrow_mut().push(value.to_string())
+    });
+    count.value_mut().assign(1000);
+
+    assert_eq!(*log.borrow(), ["1000"]);
+}
+
+#[test]
+fn states_created_after_a_dropped_one_still_publish() {
+    let log = Log::default();
+
+    let _created = batch(|| {
+        for _ in 0..8 {
+            let dropped: State<u32> = 0.into();
+            dropped.value_mut().assign(1);
+        }
+
+        // Likely to reuse the memory of the dropped states.
+        (0..8)
+            .map(|index| {
+                let created: State<u32> = 0.into();
+                let subscription = created.subscribe({
+                    let log = log.clone();
+                    move |value| log.borrow_mut().push(value.to_string())
+                });
+                created.value_mut().assign(index + 1);
+
+                (created, subscription)
+            })
+            .collect::<Vec<_>>()
+    });
+
+    assert_eq!(*log.borrow(), ["1", "2", "3", "4", "5", "6", "7", "8"]);
+}
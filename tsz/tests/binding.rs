@@ -0,0 +1,59 @@
+use std::{cell::RefCell, rc::Rc};
+
+use tsz::{Memo, ReadBinding, State, ToBinding, WriteBinding};
+
+#[test]
+fn read_bindings_follow_the_state() {
+    let state: State<String> = "a".to_string().into();
+    let binding = state.bind();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let _subscription = binding.subscribe({
+        let log = log.clone();
+        move |value: &String| log.borrow_mut().push(value.clone())
+    });
+    state.value_mut().assign("b".to_string());
+
+    assert_eq!(binding.cloned(), "b");
+    assert_eq!(binding.with(String::len), 1);
+    assert_eq!(*log.borrow(), ["b"]);
+}
+
+#[test]
+fn write_bindings_assign_the_state() {
+    let state: State<u32> = 1.into();
+    let binding = state.bind_mut();
+
+    binding.value_mut().assign(2);
+    assert_eq!(state.value(), 2);
+
+    binding.update(|value| *value *= 10);
+    assert_eq!(state.value(), 20);
+
+    // A read-only binding taken from it still follows the state.
+    let read = binding.read_only();
+    binding.value_mut().assign(3);
+    assert_eq!(read.value(), 3);
+}
+
+#[test]
+fn children_get_the_binding_they_ask_for() {
+    let state: State<u32> = 1.into();
+    let doubled = Memo::new({
+        let state = state.bind();
+        move || state.value() * 2
+    });
+
+    let write: WriteBinding<u32> = state.to_binding();
+    let from_state: ReadBinding<u32> = state.to_binding();
+    let from_write: ReadBinding<u32> = write.to_binding();
+    let from_memo: ReadBinding<u32> = doubled.to_binding();
+    let again: WriteBinding<u32> = write.to_binding();
+
+    again.value_mut().assign(5);
+
+    assert_eq!(write.read_only().value(), 5);
+    assert_eq!(from_state.value(), 5);
+    assert_eq!(from_write.value(), 5);
+    assert_eq!(from_memo.value(), 10);
+}
@@ -0,0 +1,144 @@
+use std::{cell::RefCell, rc::Rc};
+
+use tsz::{batch, effect, MapDiff, StateMap, StateVec, Subscription, VecDiff};
+
+fn record_vec(items: &StateVec<char>) -> (Rc<RefCell<Vec<VecDiff<char>>>>, Subscription) {
+    let diffs = Rc::new(RefCell::new(Vec::new()));
+    let subscription = items.subscribe_diff({
+        let diffs = diffs.clone();
+        move |diff| diffs.borrow_mut().push(diff.clone())
+    });
+
+    (diffs, subscription)
+}
+
+#[test]
+fn vec_changes_are_published_as_diffs() {
+    let items: StateVec<char> = vec!['a', 'b'].into();
+    let (diffs, _subscription) = record_vec(&items);
+
+    items.push('c');
+    items.insert(0, 'z');
+    assert_eq!(items.remove(1), 'a');
+    items.move_item(0, 2);
+    items.set(0, 'B');
+    assert_eq!(items.update(1, |item| std::mem::replace(item, 'C')), 'c');
+    assert_eq!(items.pop(), Some('z'));
+    items.clear();
+
+    assert_eq!(
+        *diffs.borrow(),
+        [
+            VecDiff::Insert {
+                index: 2,
+                value: 'c'
+            },
+            VecDiff::Insert {
+                index: 0,
+                value: 'z'
+            },
+            VecDiff::Remove { index: 1 },
+            VecDiff::Move { from: 0, to: 2 },
+            VecDiff::Update {
+                index: 0,
+                value: 'B'
+            },
+            VecDiff::Update {
+                index: 1,
+                value: 'C'
+            },
+            VecDiff::Remove { index: 2 },
+            VecDiff::Clear,
+        ]
+    );
+    assert!(items.is_empty());
+}
+
+#[test]
+fn diffs_made_in_a_batch_are_delivered_in_order() {
+    let items: StateVec<char> = StateVec::new();
+    let (diffs, _subscription) = record_vec(&items);
+
+    batch(|| {
+        items.push('a');
+        items.push('b');
+        assert!(diffs.borrow().is_empty());
+    });
+
+    assert_eq!(diffs.borrow().len(), 2);
+    assert_eq!(*items.borrow(), ['a', 'b']);
+}
+
+#[test]
+fn the_whole_vec_is_tracked() {
+    let items: StateVec<char> = StateVec::new();
+    let lengths = Rc::new(RefCell::new(Vec::new()));
+
+    let _effect = effect({
+        let (items, lengths) = (items.bind(), lengths.clone());
+        move || lengths.borrow_mut().push(items.with(Vec::len))
+    });
+    items.push('a');
+    items.push('b');
+
+    assert_eq!(*lengths.borrow(), [0, 1, 2]);
+}
+
+#[test]
+fn map_changes_are_published_as_diffs() {
+    let entries: StateMap<&str, u32> = StateMap::new();
+    let diffs = Rc::new(RefCell::new(Vec::new()));
+    let _subscription = entries.subscribe_diff({
+        let diffs = diffs.clone();
+        move |diff| diffs.borrow_mut().push(diff.clone())
+    });
+
+    assert_eq!(entries.insert("a", 1), None);
+    assert_eq!(entries.insert("b", 2), None);
+    assert_eq!(entries.insert("a", 10), Some(1));
+    assert_eq!(entries.update(&"b", |value| *value += 1), Some(()));
+    assert_eq!(entries.remove(&"a"), Some(10));
+    entries.clear();
+
+    assert_eq!(
+        *diffs.borrow(),
+        [
+            MapDiff::Insert { key: "a", value: 1 },
+            MapDiff::Insert { key: "b", value: 2 },
+            MapDiff::Update {
+                key: "a",
+                value: 10
+            },
+            MapDiff::Update { key: "b", value: 3 },
+            MapDiff::Remove { key: "a" },
+            MapDiff::Clear,
+        ]
+    );
+}
+
+#[test]
+fn missing_map_keys_publish_nothing() {
+    let entries: StateMap<&str, u32> = StateMap::new();
+    let publishes = Rc::new(RefCell::new(0));
+    let _subscription = entries.subscribe({
+        let publishes = publishes.clone();
+        move |_| *publishes.borrow_mut() += 1
+    });
+
+    assert_eq!(entries.update(&"missing", |value| *value += 1), None);
+    assert_eq!(entries.remove(&"missing"), None);
+
+    assert_eq!(*publishes.borrow(), 0);
+}
+
+#[test]
+fn map_keys_keep_their_position() {
+    let entries: StateMap<&str, u32> = StateMap::new();
+    entries.insert("a", 1);
+    entries.insert("b", 2);
+    entries.insert("a", 3);
+
+    let keys: Vec<_> = entries.borrow().keys().copied().collect();
+    assert_eq!(keys, ["a", "b"]);
+    assert_eq!(entries.get(&"a"), Some(3));
+}
@@ -0,0 +1,7 @@
+//! Checks the errors `view!` reports for mistakes, and where it points them.
+
+#[test]
+fn view_errors() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
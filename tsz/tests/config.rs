@@ -0,0 +1,137 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use tsz::{
+    config::{AppConfig, Slice},
+    Scope,
+};
+
+const COUNT: Slice<u64> = Slice::new("count");
+const NAMES: Slice<Vec<String>> = Slice::new("names");
+
+struct Increment(u64);
+struct AddName(&'static str);
+
+fn store() -> AppConfig {
+    let config = AppConfig::new();
+    config.add_slice(COUNT, 0);
+    config.add_slice(NAMES, Vec::new());
+    config.add_reducer(COUNT, |count, Increment(by): &Increment| *count += by);
+    config.add_reducer(NAMES, |names, AddName(name): &AddName| {
+        names.push(name.to_string())
+    });
+
+    config
+}
+
+#[test]
+fn reducers_update_their_slice() {
+    let config = store();
+
+    config.dispatch(Increment(2));
+    config.dispatch(Increment(3));
+    config.dispatch(AddName("Ada"));
+
+    assert_eq!(config.slice(COUNT).read_only().value(), 5);
+    assert_eq!(*config.slice(NAMES).read_only().borrow(), ["Ada"]);
+}
+
+#[test]
+fn every_reducer_of_an_action_runs_in_one_batch() {
+    let config = store();
+    config.add_reducer(NAMES, |names, Increment(by): &Increment| {
+        names.push(by.to_string())
+    });
+
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let _subscription = config.slice(COUNT).read_only().subscribe({
+        let (names, seen) = (config.slice(NAMES).read_only(), seen.clone());
+        move |count| seen.borrow_mut().push((*count, names.borrow().len()))
+    });
+    config.dispatch(Increment(4));
+
+    // The count's subscriber already sees the name added by the other reducer.
+    assert_eq!(*seen.borrow(), [(4, 1)]);
+}
+
+#[test]
+fn subscribers_of_a_slice_dispatch_actions() {
+    let config = store();
+
+    // Rounds the count up to an even number.
+    let _subscription = config.slice(COUNT).read_only().subscribe({
+        let config = config.clone();
+        move |count| {
+            if count % 2 == 1 {
+                config.dispatch(Increment(1));
+            }
+        }
+    });
+    config.dispatch(Increment(3));
+
+    assert_eq!(config.slice(COUNT).read_only().value(), 4);
+}
+
+#[test]
+fn actions_without_reducers_are_ignored() {
+    let config = store();
+    config.dispatch("unknown");
+
+    assert_eq!(config.slice(COUNT).read_only().value(), 0);
+}
+
+#[test]
+fn selectors_only_notify_when_the_selection_changes() {
+    let config = store();
+    let even = config.select(COUNT, |count| count % 2 == 0);
+
+    let notified = Rc::new(Cell::new(0));
+    let _subscription = even.subscribe({
+        let notified = notified.clone();
+        move |_| notified.set(notified.get() + 1)
+    });
+
+    config.dispatch(Increment(2));
+    assert!(even.value());
+    assert_eq!(notified.get(), 0);
+
+    config.dispatch(Increment(1));
+    assert!(!even.value());
+    assert_eq!(notified.get(), 1);
+}
+
+#[test]
+fn slices_are_written_directly() {
+    let config = store();
+    config.slice(COUNT).value_mut().assign(7);
+
+    assert_eq!(config.slice(COUNT).read_only().value(), 7);
+}
+
+#[test]
+fn the_store_is_shared_through_a_context() {
+    let config = store();
+    let scope = Scope::new();
+    scope.enter(|| config.provide());
+
+    scope
+        .child()
+        .enter(|| AppConfig::current().dispatch(Increment(1)));
+
+    assert_eq!(config.slice(COUNT).read_only().value(), 1);
+}
+
+#[test]
+#[should_panic(expected = "A slice named `count` was already added")]
+fn slices_are_added_once() {
+    store().add_slice(COUNT, 1);
+}
+
+#[test]
+#[should_panic(expected = "doesn't hold a")]
+fn slices_are_read_with_their_type() {
+    let config = store();
+    config.slice(Slice::<u32>::new("count"));
+}
@@ -0,0 +1,88 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use tsz::{provide_context, try_use_context, use_context, Scope};
+
+#[derive(Clone, Debug, PartialEq)]
+struct Theme(&'static str);
+
+#[derive(Clone, Debug, PartialEq)]
+struct Locale(&'static str);
+
+#[test]
+fn nested_scopes_see_the_contexts_of_their_ancestors() {
+    let root = Scope::new();
+    root.enter(|| {
+        provide_context(Theme("dark"));
+        provide_context(Locale("en"));
+    });
+
+    let child = root.child();
+    let grandchild = child.child();
+    child.enter(|| provide_context(Theme("light")));
+
+    // The closest provider wins, and other types still come from further up.
+    grandchild.enter(|| {
+        assert_eq!(use_context::<Theme>(), Theme("light"));
+        assert_eq!(use_context::<Locale>(), Locale("en"));
+    });
+    root.enter(|| assert_eq!(use_context::<Theme>(), Theme("dark")));
+}
+
+#[test]
+fn contexts_stay_within_their_subtree() {
+    let root = Scope::new();
+    let first = root.child();
+    let second = root.child();
+    first.enter(|| provide_context(Theme("dark")));
+
+    second.enter(|| assert_eq!(try_use_context::<Theme>(), None));
+    root.enter(|| assert_eq!(try_use_context::<Theme>(), None));
+}
+
+#[test]
+fn providing_again_replaces_the_value() {
+    let scope = Scope::new();
+    scope.enter(|| {
+        provide_context(Theme("dark"));
+        provide_context(Theme("light"));
+
+        assert_eq!(use_context::<Theme>(), Theme("light"));
+    });
+}
+
+#[test]
+fn contexts_are_only_available_inside_a_scope() {
+    let scope = Scope::new();
+    scope.enter(|| provide_context(Theme("dark")));
+
+    assert_eq!(try_use_context::<Theme>(), None);
+}
+
+#[test]
+fn missing_contexts_name_their_type() {
+    let panicked = panic::catch_unwind(|| Scope::new().enter(use_context::<Theme>));
+
+    let message = panicked.unwrap_err();
+    let message = message.downcast_ref::<String>().unwrap();
+    assert!(message.starts_with("No context of type `context::Theme`"));
+}
+
+#[test]
+#[should_panic(expected = "was called outside of a view")]
+fn providing_outside_of_a_scope_panics() {
+    provide_context(Theme("dark"));
+}
+
+#[test]
+fn a_panicking_scope_is_left() {
+    let scope = Scope::new();
+    let panicked = panic::catch_unwind(AssertUnwindSafe(|| {
+        scope.enter(|| {
+            provide_context(Theme("dark"));
+            panic!("failed");
+        })
+    }));
+    assert!(panicked.is_err());
+
+    assert_eq!(try_use_context::<Theme>(), None);
+}
@@ -0,0 +1,33 @@
+#![cfg(debug_assertions)]
+
+use tsz::{
+    debug::{self, Origin},
+    effect, State,
+};
+
+const ORIGIN: Origin = Origin {
+    view: "Panel",
+    element: "_e1",
+};
+
+#[test]
+fn effects_tag_the_subscriptions_of_every_run() {
+    let open: State<bool> = State::from(false).named("open");
+    let title: State<String> = State::from("Settings".to_string()).named("title");
+
+    let _effect = debug::with_origin(ORIGIN, || {
+        let (open, title) = (open.bind(), title.bind());
+        effect(move || {
+            if open.value() {
+                title.borrow();
+            }
+        })
+    });
+    // Subscribed on the second run, outside of `with_origin`.
+    open.value_mut().assign(true);
+
+    let graph = debug::dump_graph();
+    let title_node = &graph[graph.find("title").unwrap()..];
+    assert!(title_node.contains(&ORIGIN.to_string()));
+    assert!(!graph.contains("<anonymous>"));
+}
@@ -0,0 +1,216 @@
+use std::{
+    cell::RefCell,
+    panic::{self, AssertUnwindSafe},
+    rc::Rc,
+};
+
+use tsz::{State, StateVec, Subscription};
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+#[test]
+fn subscribers_read_the_state_they_are_called_for() {
+    let state: State<String> = "a".to_string().into();
+    let log = Log::default();
+
+    let _subscription = state.subscribe({
+        let (read, log) = (state.bind(), log.clone());
+        move |value| log.borrow_mut().push(format!("{value} {}", read.cloned()))
+    });
+    state.value_mut().assign("b".to_string());
+
+    assert_eq!(*log.borrow(), ["b b"]);
+}
+
+#[test]
+fn assignments_from_subscribers_are_published_after_them() {
+    let state: State<u32> = 0.into();
+    let log = Log::default();
+
+    let _first = state.subscribe({
+        let (write, log) = (state.bind_mut(), log.clone());
+        move |value| {
+            log.borrow_mut().push(format!("first {value}"));
+            if *value == 1 {
+                write.value_mut().assign(2);
+            }
+        }
+    });
+    let _second = state.subscribe({
+        let log = log.clone();
+        move |value| log.borrow_mut().push(format!("second {value}"))
+    });
+    state.value_mut().assign(1);
+
+    assert_eq!(
+        *log.borrow(),
+        ["first 1", "second 1", "first 2", "second 2"]
+    );
+    assert_eq!(state.value(), 2);
+}
+
+#[test]
+fn subscribers_update_their_state_in_place() {
+    let state: State<Vec<u32>> = vec![].into();
+    let log = Log::default();
+
+    let _subscription = state.subscribe({
+        let (write, log) = (state.bind_mut(), log.clone());
+        move |items| {
+            log.borrow_mut().push(format!("{items:?}"));
+            if items.len() == 1 {
+                write.update(|items| items.push(2));
+                write.value_mut().update(|items| items.push(3));
+                assert_eq!(write.value_mut().replace_latest(vec![4]), [1, 2, 3]);
+                write.update(|items| items.push(5));
+            }
+        }
+    });
+    state.update(|items| items.push(1));
+
+    assert_eq!(*log.borrow(), ["[1]", "[4, 5]"]);
+}
+
+#[test]
+fn states_without_clone_are_replaced_and_taken() {
+    #[derive(Default, Debug, PartialEq)]
+    struct Token(u32);
+
+    let state: State<Token> = Token(1).into();
+    let log = Log::default();
+    let _subscription = state.subscribe({
+        let log = log.clone();
+        move |token| log.borrow_mut().push(format!("{}", token.0))
+    });
+
+    assert_eq!(state.value_mut().replace(Token(2)), Token(1));
+    assert_eq!(state.value_mut().take(), Token(2));
+
+    assert_eq!(*log.borrow(), ["2", "0"]);
+}
+
+#[test]
+#[should_panic(expected = "use `replace_latest` instead")]
+fn subscribers_replace_their_state_with_replace_latest() {
+    let state: State<u32> = 0.into();
+    let _subscription = state.subscribe({
+        let write = state.bind_mut();
+        move |_| {
+            write.value_mut().replace(2);
+        }
+    });
+
+    state.value_mut().assign(1);
+}
+
+#[test]
+fn collections_are_changed_by_their_subscribers() {
+    let items = Rc::new(StateVec::<u32>::new());
+    let diffs = Log::default();
+
+    let _subscription = items.subscribe({
+        let items = Rc::downgrade(&items);
+        move |values| {
+            if values.len() == 1 {
+                let items = items.upgrade().unwrap();
+                items.push(values[0] + 1);
+                assert_eq!(
+                    items.len(),
+                    1,
+                    "The push is published once the dispatch ends"
+                );
+            }
+        }
+    });
+    let _diffs = items.subscribe_diff({
+        let diffs = diffs.clone();
+        move |diff| diffs.borrow_mut().push(format!("{diff:?}"))
+    });
+    items.push(1);
+
+    assert_eq!(*items.borrow(), [1, 2]);
+    assert_eq!(
+        *diffs.borrow(),
+        [
+            "Insert { index: 0, value: 1 }",
+            "Insert { index: 1, value: 2 }"
+        ]
+    );
+}
+
+#[test]
+fn subscribers_subscribe_and_unsubscribe_during_a_publish() {
+    let state: State<u32> = 0.into();
+    let log = Log::default();
+    let added: Rc<RefCell<Vec<Subscription>>> = Rc::default();
+    let removed: Rc<RefCell<Option<Subscription>>> = Rc::default();
+
+    let _first = state.subscribe({
+        let (state, log) = (state.bind(), log.clone());
+        let (added, removed) = (added.clone(), removed.clone());
+        move |value| {
+            log.borrow_mut().push(format!("first {value}"));
+            removed.borrow_mut().take();
+
+            let log = log.clone();
+            let subscription =
+                state.subscribe(move |value| log.borrow_mut().push(format!("added {value}")));
+            added.borrow_mut().push(subscription);
+        }
+    });
+    *removed.borrow_mut() = Some(state.subscribe({
+        let log = log.clone();
+        move |value| log.borrow_mut().push(format!("removed {value}"))
+    }));
+
+    // Subscribers added during a publish are first called on the next one, and the ones
+    // removed during it aren't called anymore.
+    state.value_mut().assign(1);
+    state.value_mut().assign(2);
+
+    assert_eq!(*log.borrow(), ["first 1", "first 2", "added 2"]);
+}
+
+#[test]
+fn update_loops_are_reported() {
+    let state: State<Vec<u32>> = vec![].into();
+
+    let _subscription = state.subscribe({
+        let write = state.bind_mut();
+        move |_| write.update(|items| items.push(0))
+    });
+    let panicked = panic::catch_unwind(AssertUnwindSafe(|| state.update(|items| items.push(0))));
+
+    let message = panicked.unwrap_err();
+    assert!(message
+        .downcast_ref::<String>()
+        .unwrap()
+        .starts_with("Update loop detected"));
+}
+
+#[test]
+fn recovers_after_a_subscriber_panics() {
+    let state: State<u32> = 0.into();
+    let log = Log::default();
+
+    let failing = state.subscribe({
+        let write = state.bind_mut();
+        move |_| {
+            write.value_mut().assign(100);
+            panic!("subscriber failed");
+        }
+    });
+    let panicked = panic::catch_unwind(AssertUnwindSafe(|| state.value_mut().assign(1)));
+    assert!(panicked.is_err());
+    drop(failing);
+
+    // The state isn't left dispatching, so writes are applied and published right away.
+    let _subscription = state.subscribe({
+        let log = log.clone();
+        move |value| log.borrow_mut().push(value.to_string())
+    });
+    state.update(|value| *value += 1);
+
+    assert_eq!(state.value(), 2);
+    assert_eq!(*log.borrow(), ["2"]);
+}
@@ -0,0 +1,125 @@
+use std::{
+    cell::RefCell,
+    panic::{self, AssertUnwindSafe},
+    rc::Rc,
+};
+
+use tsz::{effect, on_cleanup, Scope, State};
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+#[test]
+fn reruns_with_cleanups_until_disposed() {
+    let count: State<u32> = 1.into();
+    let log = Log::default();
+
+    let effect = effect({
+        let (count, log) = (count.bind(), log.clone());
+        move || {
+            let value = count.value();
+            log.borrow_mut().push(format!("run {value}"));
+
+            let log = log.clone();
+            on_cleanup(move || log.borrow_mut().push(format!("cleanup {value}")));
+        }
+    });
+
+    count.value_mut().assign(2);
+    effect.dispose();
+    count.value_mut().assign(3);
+
+    assert_eq!(*log.borrow(), ["run 1", "cleanup 1", "run 2", "cleanup 2"]);
+}
+
+#[test]
+fn writes_to_the_state_it_reads() {
+    let count: State<u32> = 50.into();
+    let runs = Log::default();
+
+    // Clamps the count, including the value it starts with.
+    let _effect = effect({
+        let (count, runs) = (count.bind_mut(), runs.clone());
+        move || {
+            let value = count.value();
+            runs.borrow_mut().push(value.to_string());
+            if value > 10 {
+                count.value_mut().assign(10);
+            }
+        }
+    });
+    assert_eq!(count.value(), 10);
+
+    count.value_mut().assign(20);
+    assert_eq!(count.value(), 10);
+
+    assert_eq!(*runs.borrow(), ["50", "10", "20", "10"]);
+}
+
+#[test]
+fn is_disposed_with_its_scope() {
+    let count: State<u32> = 1.into();
+    let log = Log::default();
+
+    let scope = Scope::new();
+    scope.effect({
+        let (count, log) = (count.bind(), log.clone());
+        move || log.borrow_mut().push(count.value().to_string())
+    });
+
+    count.value_mut().assign(2);
+    scope.dispose();
+    count.value_mut().assign(3);
+
+    assert_eq!(*log.borrow(), ["1", "2"]);
+}
+
+#[test]
+fn a_panicking_run_stops_tracking() {
+    let (fail, other): (State<bool>, State<u32>) = (false.into(), 0.into());
+    let runs = Log::default();
+
+    let _effect = effect({
+        let (fail, runs) = (fail.bind(), runs.clone());
+        move || {
+            runs.borrow_mut().push("run".to_string());
+            assert!(!fail.value(), "failed");
+        }
+    });
+    let panicked = panic::catch_unwind(AssertUnwindSafe(|| fail.value_mut().assign(true)));
+    assert!(panicked.is_err());
+
+    // Read outside of any effect, so it must not become a dependency of the one that failed.
+    other.value();
+    other.value_mut().assign(1);
+
+    assert_eq!(*runs.borrow(), ["run", "run"]);
+}
+
+#[test]
+fn cleanups_survive_a_panicking_run() {
+    let fail: State<bool> = false.into();
+    let log = Log::default();
+
+    let effect = effect({
+        let (fail, log) = (fail.bind(), log.clone());
+        move || {
+            let failing = fail.value();
+            let log = log.clone();
+            on_cleanup(move || log.borrow_mut().push(format!("cleanup {failing}")));
+            assert!(!failing, "failed");
+        }
+    });
+    let panicked = panic::catch_unwind(AssertUnwindSafe(|| fail.value_mut().assign(true)));
+    assert!(panicked.is_err());
+
+    // Outside of an effect, so dropped rather than kept by the run that failed.
+    let outside = Rc::new(());
+    on_cleanup({
+        let outside = outside.clone();
+        move || drop(outside)
+    });
+    assert_eq!(Rc::strong_count(&outside), 1);
+
+    effect.dispose();
+    assert_eq!(*log.borrow(), ["cleanup false", "cleanup true"]);
+}
@@ -0,0 +1,113 @@
+use std::{cell::RefCell, rc::Rc};
+
+use tsz::{State, Subscription};
+
+/// Subscribes to `state`, counting its publishes.
+fn count_publishes<T: 'static>(state: &State<T>) -> (Rc<RefCell<u32>>, Subscription) {
+    let publishes = Rc::new(RefCell::new(0));
+    let subscription = state.subscribe({
+        let publishes = publishes.clone();
+        move |_| *publishes.borrow_mut() += 1
+    });
+
+    (publishes, subscription)
+}
+
+#[test]
+fn assign_skips_equal_values() {
+    let state: State<String> = "a".to_string().into();
+    let (publishes, _subscription) = count_publishes(&state);
+
+    state.value_mut().assign("a".to_string());
+    state.value_mut().assign("b".to_string());
+    state.value_mut().assign("b".to_string());
+
+    assert_eq!(*publishes.borrow(), 1);
+    assert_eq!(state.cloned(), "b");
+
+    let flag: State<bool> = true.into();
+    let (publishes, _subscription) = count_publishes(&flag);
+    flag.value_mut().assign(true);
+    assert_eq!(*publishes.borrow(), 0);
+}
+
+#[test]
+fn set_force_always_publishes() {
+    let state: State<u32> = 1.into();
+    let (publishes, _subscription) = count_publishes(&state);
+
+    state.value_mut().set_force(1);
+    state.value_mut().set_force(1);
+
+    assert_eq!(*publishes.borrow(), 2);
+}
+
+#[test]
+fn states_without_partial_eq_can_be_set() {
+    struct Opaque(u32);
+
+    let state: State<Opaque> = Opaque(1).into();
+    let (publishes, _subscription) = count_publishes(&state);
+
+    state.value_mut().set(Opaque(1));
+    state.value_mut().set(Opaque(2));
+
+    assert_eq!(*publishes.borrow(), 2);
+    assert_eq!(state.borrow().0, 2);
+}
+
+#[test]
+fn custom_equality_is_used_by_every_assignment() {
+    let state = State::with_equality(1.0_f64, |a, b| (a - b).abs() < 0.1);
+    let (publishes, _subscription) = count_publishes(&state);
+
+    state.value_mut().assign(1.05);
+    state.value_mut().add(-0.1);
+    assert_eq!(*publishes.borrow(), 0);
+
+    state.value_mut().assign(2.0);
+    assert_eq!(*publishes.borrow(), 1);
+
+    // Forcing bypasses the equality.
+    state.value_mut().set_force(2.0);
+    assert_eq!(*publishes.borrow(), 2);
+}
+
+#[test]
+fn custom_equality_works_without_partial_eq() {
+    struct Version(u32, &'static str);
+
+    let state = State::with_equality(Version(1, "first"), |a, b| a.0 == b.0);
+    let (publishes, _subscription) = count_publishes(&state);
+
+    state.value_mut().set(Version(1, "again"));
+    state.value_mut().set(Version(2, "second"));
+
+    assert_eq!(*publishes.borrow(), 1);
+    assert_eq!(state.borrow().1, "second");
+}
+
+#[test]
+fn operators_publish_changed_results() {
+    let count: State<i32> = 3.into();
+    let (publishes, _subscription) = count_publishes(&count);
+
+    count.value_mut().add(2);
+    let mut value = count.value_mut();
+    value *= 4;
+    count.value_mut().negate();
+
+    assert_eq!(count.value(), -20);
+    assert_eq!(*publishes.borrow(), 3);
+
+    // Operators that leave the value unchanged don't publish.
+    count.value_mut().add(0);
+    let mut value = count.value_mut();
+    value *= 1;
+    assert_eq!(*publishes.borrow(), 3);
+
+    let flag: State<bool> = false.into();
+    flag.value_mut().toggle();
+    flag.value_mut().invert();
+    assert!(!flag.value());
+}
@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use tsz::{
+    batch,
+    clock::{self, FakeClock},
+    HistoryState,
+};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn undoes_and_redoes_changes() {
+    let history = HistoryState::new(0);
+    history.value_mut().assign(1);
+    history.value_mut().assign(2);
+
+    assert!(history.undo());
+    assert_eq!(history.value(), 1);
+    assert!(history.undo());
+    assert_eq!(history.value(), 0);
+    assert!(!history.undo());
+
+    assert!(history.redo());
+    assert_eq!(history.value(), 1);
+
+    // A new change drops the changes that were undone.
+    history.value_mut().assign(5);
+    assert!(!history.redo());
+    assert!(history.undo());
+    assert_eq!(history.value(), 1);
+}
+
+#[test]
+fn reports_what_can_be_undone_and_redone() {
+    let history = HistoryState::new("a".to_string());
+    let (can_undo, can_redo) = (history.can_undo(), history.can_redo());
+    assert!(!can_undo.value() && !can_redo.value());
+
+    history.value_mut().assign("b".to_string());
+    assert!(can_undo.value() && !can_redo.value());
+
+    history.undo();
+    assert!(!can_undo.value() && can_redo.value());
+
+    history.redo();
+    history.clear_history();
+    assert!(!can_undo.value() && !can_redo.value());
+    assert_eq!(history.cloned(), "b");
+}
+
+#[test]
+fn keeps_at_most_its_capacity() {
+    let history = HistoryState::with_capacity(0, 2);
+    for value in 1..=4 {
+        history.value_mut().assign(value);
+    }
+
+    while history.undo() {}
+    assert_eq!(history.value(), 2);
+}
+
+#[test]
+fn publishes_of_the_same_value_are_not_recorded() {
+    let history = HistoryState::new(0);
+    history.value_mut().set_force(0);
+    history.value_mut().assign(1);
+    history.value_mut().set_force(1);
+
+    assert!(history.undo());
+    assert!(!history.undo());
+}
+
+#[test]
+fn a_batch_is_undone_at_once() {
+    let history = HistoryState::new(0);
+    batch(|| {
+        history.value_mut().assign(1);
+        history.value_mut().assign(2);
+    });
+
+    assert!(history.undo());
+    assert_eq!(history.value(), 0);
+}
+
+#[test]
+fn coalesces_changes_made_close_together() {
+    let clock = FakeClock::new();
+    clock::set_clock(clock.clone());
+
+    let history = HistoryState::new(String::new()).coalesce_within(ms(100));
+    for text in ["h", "he", "hel"] {
+        history.value_mut().assign(text.to_string());
+        clock.advance(ms(50));
+    }
+    clock.advance(ms(100));
+    history.value_mut().assign("hello".to_string());
+
+    assert!(history.undo());
+    assert_eq!(history.cloned(), "hel");
+    assert!(history.undo());
+    assert_eq!(history.cloned(), "");
+
+    // After an undo, the next change starts a new entry however soon it comes.
+    history.redo();
+    history.value_mut().assign("help".to_string());
+    assert!(history.undo());
+    assert_eq!(history.cloned(), "hel");
+}
+
+#[test]
+fn coalesces_by_the_default_clock() {
+    let history = HistoryState::new(0).coalesce_within(ms(10));
+    history.value_mut().assign(1);
+    std::thread::sleep(ms(20));
+    history.value_mut().assign(2);
+
+    assert!(history.undo());
+    assert_eq!(history.value(), 1);
+}
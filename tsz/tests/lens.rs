@@ -0,0 +1,108 @@
+use std::{cell::RefCell, rc::Rc};
+
+use tsz::State;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Form {
+    name: String,
+    age: u32,
+}
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+#[test]
+fn read_lenses_follow_their_field() {
+    let form: State<Form> = Form::default().into();
+    let name = form.bind().map_field(|form| &form.name);
+    let log = Log::default();
+
+    let _subscription = name.subscribe({
+        let log = log.clone();
+        move |name| log.borrow_mut().push(name.clone())
+    });
+    form.value_mut().assign(Form {
+        name: "Ada".to_string(),
+        age: 36,
+    });
+
+    assert_eq!(name.cloned(), "Ada");
+    assert_eq!(*log.borrow(), ["Ada"]);
+}
+
+#[test]
+fn writes_notify_the_state_and_its_lenses() {
+    let form: State<Form> = Form::default().into();
+    let age = form
+        .bind_mut()
+        .map_field(|form| &form.age, |form| &mut form.age);
+    let log = Log::default();
+
+    let _form = form.subscribe({
+        let log = log.clone();
+        move |form| log.borrow_mut().push(format!("form {}", form.age))
+    });
+    let _age = age.read_only().subscribe({
+        let log = log.clone();
+        move |age| log.borrow_mut().push(format!("age {age}"))
+    });
+
+    age.value_mut().assign(1);
+    age.update(|age| *age += 1);
+    let mut value = age.value_mut();
+    value += 1;
+
+    assert_eq!(form.borrow().age, 3);
+    assert_eq!(
+        *log.borrow(),
+        ["form 1", "age 1", "form 2", "age 2", "form 3", "age 3"]
+    );
+}
+
+#[test]
+fn lenses_focus_further() {
+    #[derive(Clone, Default)]
+    struct Settings {
+        form: Form,
+    }
+
+    let settings: State<Settings> = Settings::default().into();
+    let name = settings
+        .bind_mut()
+        .map_field(|settings| &settings.form, |settings| &mut settings.form)
+        .map_field(|form| &form.name, |form| &mut form.name);
+
+    name.update(|name| name.push_str("Grace"));
+
+    assert_eq!(settings.borrow().form.name, "Grace");
+}
+
+#[test]
+fn subscribers_of_the_state_write_through_a_lens() {
+    let form: State<Form> = Form::default().into();
+    let name = form
+        .bind_mut()
+        .map_field(|form| &form.name, |form| &mut form.name);
+    let log = Log::default();
+
+    // Keeps the name in sync with the age, from inside the publish of the whole form.
+    let _subscription = form.subscribe({
+        let (name, log) = (name.clone(), log.clone());
+        move |form| {
+            log.borrow_mut().push(format!("{} {}", form.name, form.age));
+            if form.name != form.age.to_string() {
+                name.value_mut().assign(form.age.to_string());
+                assert_eq!(
+                    name.value_mut().replace(form.age.to_string()),
+                    form.age.to_string()
+                );
+            }
+        }
+    });
+    form.value_mut().assign(Form {
+        name: String::new(),
+        age: 7,
+    });
+
+    assert_eq!(form.borrow().name, "7");
+    assert_eq!(*log.borrow(), [" 7", "7 7"]);
+}
@@ -0,0 +1,123 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use tsz::{Memo, State};
+
+#[test]
+fn recomputes_when_a_dependency_changes() {
+    let count: State<u32> = 1.into();
+    let runs = Rc::new(Cell::new(0));
+
+    let doubled = Memo::new({
+        let count = count.bind();
+        let runs = runs.clone();
+        move || {
+            runs.set(runs.get() + 1);
+            count.value() * 2
+        }
+    });
+    assert_eq!(doubled.value(), 2);
+
+    count.value_mut().assign(5);
+    assert_eq!(doubled.value(), 10);
+    assert_eq!(runs.get(), 2);
+
+    // Reading the memo doesn't evaluate it again.
+    assert_eq!(doubled.value(), 10);
+    assert_eq!(runs.get(), 2);
+}
+
+#[test]
+fn only_tracks_the_states_read_last() {
+    let use_first: State<bool> = true.into();
+    let first: State<u32> = 1.into();
+    let second: State<u32> = 10.into();
+    let runs = Rc::new(Cell::new(0));
+
+    let selected = Memo::new({
+        let (use_first, first, second) = (use_first.bind(), first.bind(), second.bind());
+        let runs = runs.clone();
+        move || {
+            runs.set(runs.get() + 1);
+            if use_first.value() {
+                first.value()
+            } else {
+                second.value()
+            }
+        }
+    });
+
+    second.value_mut().assign(11);
+    assert_eq!(runs.get(), 1);
+
+    use_first.value_mut().assign(false);
+    assert_eq!(selected.value(), 11);
+
+    // `first` is no longer read, so it is no longer a dependency.
+    first.value_mut().assign(2);
+    assert_eq!(runs.get(), 2);
+
+    second.value_mut().assign(12);
+    assert_eq!(selected.value(), 12);
+    assert_eq!(runs.get(), 3);
+}
+
+#[test]
+fn notifies_only_when_the_value_changes() {
+    let count: State<u32> = 1.into();
+    let parity = Memo::new({
+        let count = count.bind();
+        move || count.value() % 2
+    });
+
+    let values = Rc::new(RefCell::new(Vec::new()));
+    let _subscription = parity.subscribe({
+        let values = values.clone();
+        move |value| values.borrow_mut().push(*value)
+    });
+
+    for value in [3, 5, 6, 8, 9] {
+        count.value_mut().assign(value);
+    }
+
+    assert_eq!(*values.borrow(), [0, 1]);
+}
+
+#[test]
+fn memos_depend_on_memos() {
+    let count: State<u32> = 1.into();
+    let doubled = Memo::new({
+        let count = count.bind();
+        move || count.value() * 2
+    });
+    let label = Memo::new({
+        let doubled = doubled.bind();
+        move || format!("{} items", doubled.value())
+    });
+
+    count.value_mut().assign(4);
+    assert_eq!(label.cloned(), "8 items");
+}
+
+#[test]
+fn stops_updating_once_dropped() {
+    let count: State<u32> = 1.into();
+    let runs = Rc::new(Cell::new(0));
+
+    let memo = Memo::new({
+        let count = count.bind();
+        let runs = runs.clone();
+        move || {
+            runs.set(runs.get() + 1);
+            count.value()
+        }
+    });
+    let binding = memo.bind();
+    drop(memo);
+
+    count.value_mut().assign(2);
+    assert_eq!(runs.get(), 1);
+    assert_eq!(binding.value(), 1);
+}
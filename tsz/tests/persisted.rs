@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use tsz::{MemoryStorage, Persisted, Schema, Storage};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Settings {
+    title: String,
+    zoom: u32,
+}
+
+fn defaults() -> Settings {
+    Settings {
+        title: "untitled".to_string(),
+        zoom: 100,
+    }
+}
+
+#[test]
+fn saves_on_change_and_restores() {
+    let storage = MemoryStorage::new();
+
+    let settings = Persisted::new("settings", defaults(), storage.clone());
+    assert_eq!(settings.cloned(), defaults());
+    assert_eq!(storage.get("settings"), None);
+
+    settings.update(|settings| settings.zoom = 150);
+    drop(settings);
+
+    let settings = Persisted::new("settings", defaults(), storage.clone());
+    assert_eq!(settings.borrow().zoom, 150);
+}
+
+#[test]
+fn migrates_older_versions() {
+    let storage = MemoryStorage::new();
+    storage.set(
+        "settings",
+        r#"{"version":0,"value":{"name":"notes","zoom":120}}"#,
+    );
+
+    let schema = Schema::new(1).migration(0, |mut value| {
+        value["title"] = value["name"].take();
+        value
+    });
+    let settings = Persisted::with_schema("settings", defaults(), storage.clone(), schema);
+
+    assert_eq!(
+        settings.cloned(),
+        Settings {
+            title: "notes".to_string(),
+            zoom: 120,
+        }
+    );
+}
+
+#[test]
+fn falls_back_to_default() {
+    let storage = MemoryStorage::new();
+
+    // Stored by a newer version of the app.
+    storage.set(
+        "settings",
+        r#"{"version":3,"value":{"title":"a","zoom":1}}"#,
+    );
+    let settings = Persisted::with_schema("settings", defaults(), storage.clone(), Schema::new(1));
+    assert_eq!(settings.cloned(), defaults());
+
+    storage.set("zoom", "not json");
+    let zoom = Persisted::new("zoom", 100u32, storage.clone());
+    assert_eq!(zoom.value(), 100);
+}
@@ -0,0 +1,120 @@
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use tsz::{task, Resource, ResourceState, State};
+
+/// A request that is answered by the test.
+struct Request<T> {
+    reply: RefCell<Option<T>>,
+    waker: RefCell<Option<Waker>>,
+}
+
+impl<T> Request<T> {
+    fn new() -> Rc<Request<T>> {
+        Rc::new(Request {
+            reply: RefCell::new(None),
+            waker: RefCell::new(None),
+        })
+    }
+
+    fn answer(&self, value: T) {
+        self.reply.replace(Some(value));
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+struct Response<T>(Rc<Request<T>>);
+
+impl<T> Future for Response<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        match self.0.reply.take() {
+            Some(value) => Poll::Ready(value),
+            None => {
+                self.0.waker.replace(Some(cx.waker().clone()));
+                Poll::Pending
+            }
+        }
+    }
+}
+
+type Requests = Rc<RefCell<Vec<(u32, Rc<Request<Result<String, String>>>)>>>;
+
+fn user_resource(id: &State<u32>) -> (Resource<String, String>, Requests) {
+    let requests = Requests::default();
+
+    let resource = Resource::new({
+        let id = id.bind();
+        let requests = requests.clone();
+        move || {
+            let request = Request::new();
+            requests.borrow_mut().push((id.value(), request.clone()));
+            Response(request)
+        }
+    });
+
+    (resource, requests)
+}
+
+#[test]
+fn loads_value_and_error() {
+    let id: State<u32> = 1.into();
+    let (user, requests) = user_resource(&id);
+
+    task::run_until_stalled();
+    assert!(user.loading().value());
+    assert_eq!(user.borrow().to_string(), "");
+
+    requests.borrow()[0].1.answer(Ok("alice".to_string()));
+    task::run_until_stalled();
+    assert!(user.ready().value());
+    assert_eq!(user.borrow().to_string(), "alice");
+
+    user.refetch();
+    assert!(user.loading().value());
+    requests.borrow()[1].1.answer(Err("not found".to_string()));
+    task::run_until_stalled();
+    assert!(user.failed().value());
+    assert_eq!(
+        *user.borrow(),
+        ResourceState::Error("not found".to_string())
+    );
+}
+
+#[test]
+fn refetches_on_source_change_and_discards_stale_requests() {
+    let id: State<u32> = 1.into();
+    let (user, requests) = user_resource(&id);
+    task::run_until_stalled();
+
+    id.value_mut().assign(2);
+    task::run_until_stalled();
+    assert_eq!(
+        requests
+            .borrow()
+            .iter()
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>(),
+        [1, 2]
+    );
+
+    // The first request finishes last, but its result belongs to a stale id.
+    requests.borrow()[1].1.answer(Ok("bob".to_string()));
+    requests.borrow()[0].1.answer(Ok("alice".to_string()));
+    task::run_until_stalled();
+    assert_eq!(user.borrow().to_string(), "bob");
+
+    // Dropping the resource cancels the request in flight.
+    id.value_mut().assign(3);
+    assert_eq!(task::run_until_stalled(), 1);
+    drop(user);
+    assert_eq!(task::run_until_stalled(), 0);
+}
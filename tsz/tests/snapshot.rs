@@ -0,0 +1,95 @@
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use serde_json::json;
+use tsz::{effect, Memo, Snapshot, State};
+
+#[derive(Snapshot)]
+struct Form {
+    count: State<u32>,
+    name: State<String>,
+    #[snapshot(skip)]
+    doubled: Memo<u32>,
+}
+
+impl Form {
+    fn new() -> Form {
+        let count: State<u32> = 1.into();
+        let doubled = Memo::new({
+            let count = count.bind();
+            move || count.value() * 2
+        });
+
+        Form {
+            count,
+            name: "Ada".to_string().into(),
+            doubled,
+        }
+    }
+}
+
+#[test]
+fn restores_what_it_snapshotted() {
+    let form = Form::new();
+    let snapshot = form.snapshot().unwrap();
+    assert_eq!(snapshot, json!({ "count": 1, "name": "Ada" }));
+
+    form.count.value_mut().assign(5);
+    form.name.value_mut().assign("Grace".to_string());
+    form.restore(snapshot).unwrap();
+
+    assert_eq!(form.count.value(), 1);
+    assert_eq!(form.name.cloned(), "Ada");
+    assert_eq!(form.doubled.value(), 2);
+}
+
+#[test]
+fn fields_missing_from_the_snapshot_are_kept() {
+    let form = Form::new();
+    form.restore(json!({ "count": 3 })).unwrap();
+
+    assert_eq!(form.count.value(), 3);
+    assert_eq!(form.name.cloned(), "Ada");
+}
+
+#[test]
+fn nothing_is_assigned_when_a_field_fails() {
+    let form = Form::new();
+
+    let result = form.restore(json!({ "count": 3, "name": 4 }));
+
+    assert!(result.is_err());
+    assert_eq!(form.count.value(), 1);
+    assert!(form.restore(json!([1, 2])).is_err());
+}
+
+#[test]
+fn every_field_is_published_at_once() {
+    let form = Form::new();
+    let seen = Rc::new(RefCell::new(Vec::new()));
+
+    let _effect = effect({
+        let (count, name, seen) = (form.count.bind(), form.name.bind(), seen.clone());
+        move || {
+            seen.borrow_mut()
+                .push(format!("{} {}", name.cloned(), count.value()))
+        }
+    });
+    form.restore(json!({ "count": 2, "name": "Grace" }))
+        .unwrap();
+
+    assert_eq!(*seen.borrow(), ["Ada 1", "Grace 2"]);
+}
+
+#[test]
+fn serialization_errors_are_returned() {
+    #[derive(Snapshot)]
+    struct Grid {
+        cells: State<BTreeMap<(u32, u32), u32>>,
+    }
+
+    let grid = Grid {
+        cells: BTreeMap::from([((0, 0), 1)]).into(),
+    };
+
+    assert!(grid.snapshot().is_err());
+}
@@ -0,0 +1,81 @@
+use std::{cell::RefCell, rc::Rc};
+
+use tsz::{Scope, State, Subscription};
+
+/// Subscribes to `state`, recording every published value in `log` with `tag`.
+fn record(state: &State<u32>, log: &Rc<RefCell<Vec<String>>>, tag: &'static str) -> Subscription {
+    let log = log.clone();
+    state.subscribe(move |value| log.borrow_mut().push(format!("{tag} {value}")))
+}
+
+#[test]
+fn dropping_the_handle_unsubscribes() {
+    let state: State<u32> = 0.into();
+    let log = Rc::default();
+
+    let first = record(&state, &log, "first");
+    let second = record(&state, &log, "second");
+    state.value_mut().assign(1);
+
+    drop(first);
+    state.value_mut().assign(2);
+
+    second.unsubscribe();
+    state.value_mut().assign(3);
+
+    assert_eq!(*log.borrow(), ["first 1", "second 1", "second 2"]);
+}
+
+#[test]
+fn forgotten_subscriptions_live_with_the_state() {
+    let state: State<u32> = 0.into();
+    let log = Rc::default();
+
+    record(&state, &log, "forgotten").forget();
+    state.value_mut().assign(1);
+    state.value_mut().assign(2);
+
+    assert_eq!(*log.borrow(), ["forgotten 1", "forgotten 2"]);
+}
+
+#[test]
+fn reused_slots_keep_subscription_order() {
+    let state: State<u32> = 0.into();
+    let log = Rc::default();
+
+    let first = record(&state, &log, "first");
+    let _second = record(&state, &log, "second");
+
+    // The third subscriber takes the slot freed by the first, but still runs last.
+    drop(first);
+    let _third = record(&state, &log, "third");
+    state.value_mut().assign(1);
+
+    // Dropping a stale handle doesn't remove the subscriber that reused its slot.
+    let stale = record(&state, &log, "stale");
+    drop(stale);
+    state.value_mut().assign(2);
+
+    assert_eq!(
+        *log.borrow(),
+        ["second 1", "third 1", "second 2", "third 2"]
+    );
+}
+
+#[test]
+fn disposing_a_scope_releases_its_subscriptions() {
+    let state: State<u32> = 0.into();
+    let log = Rc::default();
+
+    let scope = Scope::new();
+    let child = scope.child();
+    scope.own(record(&state, &log, "parent"));
+    child.own(record(&state, &log, "child"));
+    scope.own_scope(child);
+    state.value_mut().assign(1);
+
+    scope.dispose();
+    state.value_mut().assign(2);
+
+    assert_eq!(*log.borrow(), ["parent 1", "child 1"]);
+}
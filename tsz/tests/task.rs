@@ -0,0 +1,95 @@
+use std::{
+    cell::{Cell, RefCell},
+    future::{poll_fn, Future},
+    rc::Rc,
+    task::{Poll, Waker},
+};
+
+use tsz::task;
+
+/// A future that is pending until the test opens it.
+#[derive(Default)]
+struct Gate {
+    open: Cell<bool>,
+    waker: RefCell<Option<Waker>>,
+}
+
+impl Gate {
+    fn wait(self: &Rc<Self>) -> impl Future<Output = ()> {
+        let gate = self.clone();
+        poll_fn(move |cx| {
+            if gate.open.get() {
+                return Poll::Ready(());
+            }
+
+            gate.waker.replace(Some(cx.waker().clone()));
+            Poll::Pending
+        })
+    }
+
+    fn open(&self) {
+        self.open.set(true);
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+#[test]
+fn tasks_run_until_they_wait() {
+    let gate = Rc::new(Gate::default());
+    let steps = Rc::new(RefCell::new(Vec::new()));
+
+    task::spawn_local({
+        let (gate, steps) = (gate.clone(), steps.clone());
+        async move {
+            steps.borrow_mut().push("started");
+            gate.wait().await;
+            steps.borrow_mut().push("finished");
+        }
+    });
+    assert!(steps.borrow().is_empty());
+
+    assert_eq!(task::run_until_stalled(), 1);
+    assert_eq!(*steps.borrow(), ["started"]);
+
+    gate.open();
+    assert_eq!(task::run_until_stalled(), 0);
+    assert_eq!(*steps.borrow(), ["started", "finished"]);
+}
+
+#[test]
+fn tasks_spawn_tasks() {
+    let done = Rc::new(Cell::new(false));
+
+    task::spawn_local({
+        let done = done.clone();
+        async move { task::spawn_local(async move { done.set(true) }) }
+    });
+
+    assert_eq!(task::run_until_stalled(), 0);
+    assert!(done.get());
+}
+
+#[test]
+fn finished_tasks_woken_again_are_skipped() {
+    let waiting = Rc::new(Gate::default());
+    let leftover: Rc<RefCell<Option<Waker>>> = Rc::default();
+
+    // Finishes right away, leaving its waker behind.
+    task::spawn_local({
+        let leftover = leftover.clone();
+        poll_fn(move |cx| {
+            leftover.replace(Some(cx.waker().clone()));
+            Poll::Ready(())
+        })
+    });
+    task::spawn_local(waiting.wait());
+    assert_eq!(task::run_until_stalled(), 1);
+
+    // The stale wake comes first, and must not stop the other task from running.
+    leftover.take().unwrap().wake();
+    waiting.open();
+
+    assert_eq!(task::run_until_stalled(), 0);
+}
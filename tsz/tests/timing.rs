@@ -0,0 +1,120 @@
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use tsz::{
+    clock::{self, Clock, FakeClock, SystemClock},
+    task, ReadBinding, State,
+};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// Records every value published by `binding`.
+fn record(binding: &ReadBinding<u32>) -> (Rc<RefCell<Vec<u32>>>, tsz::Subscription) {
+    let values = Rc::new(RefCell::new(Vec::new()));
+    let subscription = binding.subscribe({
+        let values = values.clone();
+        move |value| values.borrow_mut().push(*value)
+    });
+
+    (values, subscription)
+}
+
+fn setup() -> (FakeClock, State<u32>) {
+    let clock = FakeClock::new();
+    clock::set_clock(clock.clone());
+
+    (clock, 0.into())
+}
+
+#[test]
+fn debounce_waits_for_changes_to_stop() {
+    let (clock, state) = setup();
+    let debounced = state.bind().debounce(ms(100));
+    let (values, _subscription) = record(&debounced);
+
+    for value in 1..=3 {
+        state.value_mut().assign(value);
+        clock.advance(ms(60));
+    }
+    assert_eq!(debounced.value(), 0);
+
+    clock.advance(ms(40));
+    assert_eq!(debounced.value(), 3);
+    assert_eq!(*values.borrow(), [3]);
+}
+
+#[test]
+fn throttle_publishes_first_and_latest() {
+    let (clock, state) = setup();
+    let throttled = state.bind().throttle(ms(100));
+    let (values, _subscription) = record(&throttled);
+
+    state.value_mut().assign(1);
+    state.value_mut().assign(2);
+    state.value_mut().assign(3);
+    assert_eq!(*values.borrow(), [1]);
+
+    clock.advance(ms(100));
+    assert_eq!(*values.borrow(), [1, 3]);
+
+    // The trailing value opened a new interval, after which changes go through right away.
+    clock.advance(ms(100));
+    state.value_mut().assign(4);
+    assert_eq!(*values.borrow(), [1, 3, 4]);
+}
+
+#[test]
+fn delay_keeps_every_change() {
+    let (clock, state) = setup();
+    let delayed = state.bind().delay(ms(50));
+    let (values, _subscription) = record(&delayed);
+
+    state.value_mut().assign(1);
+    clock.advance(ms(20));
+    state.value_mut().assign(2);
+    clock.advance(ms(30));
+    assert_eq!(*values.borrow(), [1]);
+
+    clock.advance(ms(20));
+    assert_eq!(*values.borrow(), [1, 2]);
+
+    // Dropping the binding cancels the timers that are still pending.
+    state.value_mut().assign(3);
+    drop(delayed);
+    clock.advance(ms(50));
+    assert_eq!(*values.borrow(), [1, 2]);
+}
+
+#[test]
+fn the_default_clock_moves_on_its_own() {
+    let start = clock::clock().now();
+    std::thread::sleep(ms(5));
+
+    assert!(clock::clock().now() - start >= ms(5));
+}
+
+#[test]
+fn system_timeouts_run_on_the_task_executor() {
+    let clock = SystemClock::new();
+    let fired = Rc::new(RefCell::new(Vec::new()));
+    let fire = |name: &'static str| -> Box<dyn FnOnce()> {
+        let fired = fired.clone();
+        Box::new(move || fired.borrow_mut().push(name))
+    };
+
+    clock.set_timeout(ms(10), fire("later")).forget();
+    clock.set_timeout(ms(5), fire("sooner")).forget();
+    let cancelled = clock.set_timeout(ms(5), fire("cancelled"));
+    cancelled.cancel();
+
+    // Nothing is due yet, so the executor stalls right away, and the cancelled timer is gone.
+    assert_eq!(task::run_until_stalled(), 2);
+    assert!(fired.borrow().is_empty());
+
+    // Timers only run once the executor is driven after they are due, in deadline order.
+    std::thread::sleep(ms(50));
+    assert!(fired.borrow().is_empty());
+    assert_eq!(task::run_until_stalled(), 0);
+    assert_eq!(*fired.borrow(), ["sooner", "later"]);
+}
@@ -0,0 +1,17 @@
+pub struct Counter {
+    count: tsz::State<u32>,
+}
+
+tsz::view! {
+    declare Counter;
+
+    button (click: { count = 1 }) {
+        "{$count}"
+    }
+
+    button (click: { $count + 1 = 2 }) {
+        "{$count}"
+    }
+}
+
+fn main() {}
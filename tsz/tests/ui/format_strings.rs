@@ -0,0 +1,14 @@
+pub struct Counter {
+    count: tsz::State<u32>,
+}
+
+tsz::view! {
+    declare Counter;
+
+    div {
+        "{$count:>4:x}"
+        "{$count + 1}"
+    }
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+pub struct Counter {
+    count: tsz::State<u32>,
+}
+
+tsz::view! {
+    declare Counter;
+
+    button (click: "count") {
+        "{$count}"
+    }
+
+    button (title: @increment) {
+        "{$count}"
+    }
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+pub struct Counter {
+    count: tsz::State<u32>,
+}
+
+impl Counter {
+    fn increment(&self) {
+        self.count.value_mut().add(1);
+    }
+}
+
+tsz::view! {
+    declare Counter;
+
+    div {
+        Label(@increment) {}
+        Label({ $count += 1 }) {}
+    }
+}
+
+fn main() {}
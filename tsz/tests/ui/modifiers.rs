@@ -0,0 +1,21 @@
+pub struct Counter {
+    count: tsz::State<u32>,
+}
+
+tsz::view! {
+    declare Counter;
+
+    button (click.prevnt: { $count += 1 }) {
+        "{$count}"
+    }
+
+    button (click.enter: { $count += 1 }) {
+        "{$count}"
+    }
+
+    button (title.once: "count") {
+        "{$count}"
+    }
+}
+
+fn main() {}
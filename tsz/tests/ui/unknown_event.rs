@@ -0,0 +1,19 @@
+pub struct Counter {
+    count: tsz::State<u32>,
+}
+
+impl Counter {
+    fn increment(&self) {
+        self.count.value_mut().add(1);
+    }
+}
+
+tsz::view! {
+    declare Counter;
+
+    button (clik: @increment) {
+        "{$count}"
+    }
+}
+
+fn main() {}
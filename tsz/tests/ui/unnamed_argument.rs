@@ -0,0 +1,13 @@
+pub struct Counter {
+    count: tsz::State<u32>,
+}
+
+tsz::view! {
+    declare Counter;
+
+    button ([primary]) {
+        "{$count}"
+    }
+}
+
+fn main() {}
@@ -0,0 +1,107 @@
+use proc_macro2::{Span, TokenStream};
+
+/// Collects the errors found while generating a view, so all of them are reported together
+/// instead of only the first one.
+#[derive(Default)]
+pub(crate) struct Errors(Option<syn::Error>);
+
+impl Errors {
+    pub(crate) fn push(&mut self, error: syn::Error) {
+        match &mut self.0 {
+            Some(errors) => errors.combine(error),
+            None => self.0 = Some(error),
+        }
+    }
+
+    /// Returns the generated tokens, or nothing if generating them failed.
+    pub(crate) fn or_empty(&mut self, result: syn::Result<TokenStream>) -> TokenStream {
+        result.unwrap_or_else(|error| {
+            self.push(error);
+            TokenStream::new()
+        })
+    }
+
+    pub(crate) fn finish(self) -> syn::Result<()> {
+        self.0.map_or(Ok(()), Err)
+    }
+}
+
+/// Builds an error at `span` that suggests the closest of `candidates` to `name`, if any
+/// is close enough to be a typo.
+pub(crate) fn suggest<'a>(
+    span: Span,
+    message: &str,
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> syn::Error {
+    let closest = candidates
+        .into_iter()
+        .map(|candidate| (distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min();
+
+    match closest {
+        Some((_, candidate)) => {
+            syn::Error::new(span, format!("{message}\nhelp: did you mean `{candidate}`?"))
+        }
+        None => syn::Error::new(span, message),
+    }
+}
+
+/// The Levenshtein distance between two names.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous + usize::from(a != *b);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_counts_edits() {
+        assert_eq!(distance("click", "click"), 0);
+        assert_eq!(distance("clik", "click"), 1);
+        assert_eq!(distance("clcik", "click"), 2);
+        assert_eq!(distance("", "input"), 5);
+        assert_eq!(distance("keydown", ""), 7);
+    }
+
+    #[test]
+    fn suggest_offers_the_closest_candidate() {
+        let candidates = ["click", "input", "keydown"];
+
+        let error = suggest(Span::call_site(), "unknown event", "clik", candidates);
+        assert_eq!(
+            error.to_string(),
+            "unknown event\nhelp: did you mean `click`?"
+        );
+
+        let error = suggest(Span::call_site(), "unknown event", "scroll", candidates);
+        assert_eq!(error.to_string(), "unknown event");
+    }
+
+    #[test]
+    fn suggest_prefers_the_nearest_of_several_matches() {
+        let error = suggest(
+            Span::call_site(),
+            "unknown",
+            "prevnt",
+            ["prevent", "present"],
+        );
+        assert_eq!(error.to_string(), "unknown\nhelp: did you mean `prevent`?");
+    }
+}
@@ -1,4 +1,7 @@
-use syn::{parse::Parse, Expr};
+use syn::{
+    parse::{discouraged::Speculative, Parse},
+    Expr,
+};
 
 use crate::syn_macros::*;
 
@@ -63,37 +66,94 @@ ast_enum_of_structs! {
     pub enum CoreExpr {
         Assignment(Assignment),
         StateBind(StateBind),
+        StateMethod(StateMethod),
+        StateExpr(StateExpr),
         FnBind(FnBind),
         Closure(Closure),
         Expr(Expr),
     }
 }
 
+fn peek_assign_op(input: syn::parse::ParseStream) -> bool {
+    // `==` and `=>` start with `=` too, but are not assignments.
+    if input.peek(syn::Token![==]) || input.peek(syn::Token![=>]) {
+        return false;
+    }
+
+    input.peek(syn::Token![=])
+        || input.peek(syn::Token![+=])
+        || input.peek(syn::Token![-=])
+        || input.peek(syn::Token![*=])
+        || input.peek(syn::Token![/=])
+        || input.peek(syn::Token![%=])
+        || input.peek(syn::Token![<<=])
+        || input.peek(syn::Token![>>=])
+        || input.peek(syn::Token![|=])
+        || input.peek(syn::Token![&=])
+        || input.peek(syn::Token![^=])
+}
+
+impl CoreExpr {
+    /// Parses `$state`, `$state.method(args)`, or an expression that reads states.
+    fn parse_state(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let fork = input.fork();
+        let bind: StateBind = fork.parse()?;
+        if fork.is_empty()
+            || fork.peek(syn::Token![,])
+            || fork.peek(syn::Token![;])
+            || peek_assign_op(&fork)
+        {
+            input.advance_to(&fork);
+            return Ok(CoreExpr::StateBind(bind));
+        }
+
+        let method = input.fork();
+        if let Ok(call) = method.parse::<StateMethod>() {
+            if method.is_empty() || method.peek(syn::Token![,]) || method.peek(syn::Token![;]) {
+                input.advance_to(&method);
+                return Ok(CoreExpr::StateMethod(call));
+            }
+        }
+
+        Ok(CoreExpr::StateExpr(input.parse()?))
+    }
+}
+
+impl CoreExpr {
+    /// Where the expression starts, for error messages.
+    pub fn span(&self) -> proc_macro2::Span {
+        match self {
+            CoreExpr::Assignment(assignment) => assignment.left.span(),
+            CoreExpr::StateBind(bind) => bind.bind_token.spans[0],
+            CoreExpr::StateMethod(call) => call.bind.bind_token.spans[0],
+            CoreExpr::StateExpr(expr) => expr
+                .tokens
+                .clone()
+                .into_iter()
+                .next()
+                .map_or_else(proc_macro2::Span::call_site, |token| token.span()),
+            CoreExpr::FnBind(bind) => bind.bind_token.spans[0],
+            CoreExpr::Closure(closure) => closure.brace.span,
+            CoreExpr::Expr(expr) => syn::spanned::Spanned::span(expr),
+        }
+    }
+}
+
 impl Parse for CoreExpr {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let left = if input.peek(syn::Token![$]) {
-            CoreExpr::StateBind(input.parse()?)
+            CoreExpr::parse_state(input)?
         } else if input.peek(syn::Token![@]) {
             CoreExpr::FnBind(input.parse()?)
         } else if input.peek(syn::token::Brace) {
             CoreExpr::Closure(input.parse()?)
+        } else if StateExpr::peek(input) {
+            CoreExpr::StateExpr(input.parse()?)
         } else {
             CoreExpr::Expr(input.parse()?)
         };
 
-        // let expr = input.parse()?;
-        if input.peek(syn::Token![=])
-            || input.peek(syn::Token![+=])
-            || input.peek(syn::Token![-=])
-            || input.peek(syn::Token![*=])
-            || input.peek(syn::Token![/=])
-            || input.peek(syn::Token![%=])
-            || input.peek(syn::Token![<<=])
-            || input.peek(syn::Token![>>=])
-            || input.peek(syn::Token![|=])
-            || input.peek(syn::Token![&=])
-            || input.peek(syn::Token![^=])
-        {
+        if peek_assign_op(input) {
             let op = input.parse()?;
             let right = input.parse()?;
 
@@ -151,11 +211,15 @@ ast_struct! {
 impl Parse for Closure {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let content;
+        let brace = syn::braced!(content in input);
+        let expr = content.parse()?;
 
-        Ok(Closure {
-            brace: syn::braced!(content in input),
-            expr: content.parse()?,
-        })
+        content.parse::<Option<syn::Token![;]>>()?;
+        if !content.is_empty() {
+            return Err(content.error("only one expression per handler"));
+        }
+
+        Ok(Closure { brace, expr })
     }
 }
 
@@ -190,3 +254,153 @@ impl syn::parse::Parse for FnBind {
         })
     }
 }
+
+ast_struct! {
+    /// A method called on the value of a state, e.g. `$name.push('x')`.
+    pub struct StateMethod #full {
+        pub bind: StateBind,
+        pub dot_token: syn::Token![.],
+        pub method: syn::Ident,
+        pub args: proc_macro2::TokenStream,
+    }
+}
+
+impl syn::parse::Parse for StateMethod {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let content;
+
+        let bind = input.parse()?;
+        let dot_token = input.parse()?;
+        let method = input.parse()?;
+        syn::parenthesized!(content in input);
+
+        Ok(StateMethod {
+            bind,
+            dot_token,
+            method,
+            args: content.parse()?,
+        })
+    }
+}
+
+ast_struct! {
+    /// An expression that reads states, e.g. `!$flag` or `$count + 1`, kept as tokens until
+    /// the states are substituted.
+    pub struct StateExpr #full {
+        pub tokens: proc_macro2::TokenStream,
+    }
+}
+
+impl StateExpr {
+    /// Whether the expression up to the next top-level comma reads a state.
+    fn peek(input: syn::parse::ParseStream) -> bool {
+        let fork = input.fork();
+        fork.parse::<StateExpr>()
+            .is_ok_and(|expr| contains_state(expr.tokens))
+    }
+}
+
+fn contains_state(tokens: proc_macro2::TokenStream) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Punct(punct) => punct.as_char() == '$',
+        proc_macro2::TokenTree::Group(group) => contains_state(group.stream()),
+        _ => false,
+    })
+}
+
+/// Whether the operator at `cursor` is `=` or a compound assignment such as `+=`.
+fn starts_assignment(cursor: syn::buffer::Cursor) -> bool {
+    // Operators are split into single characters, each joint with the next one.
+    let mut operator = String::new();
+    let mut rest = cursor;
+    while let Some((punct, next)) = rest.punct() {
+        operator.push(punct.as_char());
+        rest = next;
+        if punct.spacing() == proc_macro2::Spacing::Alone {
+            break;
+        }
+    }
+
+    let assignments = [
+        "<<=", ">>=", "+=", "-=", "*=", "/=", "%=", "^=", "&=", "|=", "=",
+    ];
+    assignments
+        .iter()
+        .find_map(|op| operator.strip_prefix(op))
+        .is_some_and(|rest| !rest.starts_with(['=', '>']))
+}
+
+impl syn::parse::Parse for StateExpr {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        // Groups are single tokens, so the first comma, semicolon or assignment found is the
+        // one ending the expression.
+        let tokens = input.step(|cursor| {
+            let mut tokens = proc_macro2::TokenStream::new();
+            let mut rest = *cursor;
+            // Whether the previous token is the start of an operator, like the `<` of `<=`.
+            let mut joint = false;
+            while let Some((token, next)) = rest.token_tree() {
+                if let proc_macro2::TokenTree::Punct(punct) = &token {
+                    let ends = matches!(punct.as_char(), ',' | ';') || starts_assignment(rest);
+                    if !joint && ends {
+                        break;
+                    }
+                }
+
+                joint = match &token {
+                    proc_macro2::TokenTree::Punct(punct) => {
+                        punct.spacing() == proc_macro2::Spacing::Joint
+                    }
+                    _ => false,
+                };
+                tokens.extend([token]);
+                rest = next;
+            }
+
+            Ok((tokens, rest))
+        })?;
+
+        if tokens.is_empty() {
+            return Err(input.error("expected an expression"));
+        }
+
+        Ok(StateExpr { tokens })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handlers_take_one_expression() {
+        let Err(error) = syn::parse_str::<Closure>("{ $count = 1; $flag = true }") else {
+            panic!("expected an error");
+        };
+        assert_eq!(error.to_string(), "only one expression per handler");
+
+        assert!(syn::parse_str::<Closure>("{ $count = 1; }").is_ok());
+    }
+
+    #[test]
+    fn state_exprs_end_at_assignments() {
+        let expr: CoreExpr = syn::parse_str("$flag = $count <= 2").unwrap();
+        let CoreExpr::Assignment(assignment) = expr else {
+            panic!("expected an assignment");
+        };
+        assert!(matches!(*assignment.left, CoreExpr::StateBind(_)));
+        let CoreExpr::StateExpr(right) = *assignment.right else {
+            panic!("expected a state expression");
+        };
+        assert_eq!(right.tokens.to_string(), "$ count <= 2");
+
+        let expr: CoreExpr = syn::parse_str("$count + 1 += 2").unwrap();
+        let CoreExpr::Assignment(assignment) = expr else {
+            panic!("expected an assignment");
+        };
+        let CoreExpr::StateExpr(left) = *assignment.left else {
+            panic!("expected a state expression");
+        };
+        assert_eq!(left.tokens.to_string(), "$ count + 1");
+    }
+}
@@ -1,25 +1,28 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
 
+use errors::Errors;
 use expr::BinOp;
+use modifiers::Modifiers;
 use proc_macro2::{Span, TokenStream};
-use quote::{quote, ToTokens};
-use syn::{parse_macro_input, Ident};
-
+use quote::{quote, quote_spanned, ToTokens};
+use syn::{
+    ext::IdentExt,
+    parse::discouraged::Speculative,
+    parse_macro_input, Ident,
+};
+
+mod errors;
 mod expr;
+mod modifiers;
+mod snapshot;
 mod syn_macros;
 
 mod kw {
     syn::custom_keyword!(declare);
 }
 
-mod punc {
-    syn::custom_punctuation!(FnBind, @);
-}
-
 enum ElementBody {
-    Empty {
-        semi: syn::Token![;],
-    },
+    Empty,
     Elements {
         brace_token: syn::token::Brace,
         body: Vec<Element>,
@@ -30,9 +33,8 @@ impl syn::parse::Parse for ElementBody {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let la = input.lookahead1().peek(syn::Token![;]);
         if la {
-            Ok(ElementBody::Empty {
-                semi: input.parse()?,
-            })
+            input.parse::<syn::Token![;]>()?;
+            Ok(ElementBody::Empty)
         } else {
             let content;
 
@@ -52,84 +54,115 @@ impl syn::parse::Parse for ElementBody {
     }
 }
 
-enum Value {
-    FnBind {
-        bind_tok: punc::FnBind,
-        ident: syn::Ident,
-    },
-    Expr(expr::CoreExpr),
-    Stmt {
-        group: syn::token::Brace,
-        stmt: expr::CoreExpr,
-    },
+struct KeyValue {
+    key: Option<(Ident, syn::Token![:])>,
+    directive: Option<Directive>,
+    /// The modifiers of an event, as in `click.stop: ...` or `on:item-selected.once = ...`.
+    modifiers: Vec<Ident>,
+    value: expr::CoreExpr,
 }
 
-impl Value {
-    pub fn as_fn_bind(&self) -> &syn::Ident {
-        match self {
-            Self::FnBind { ident, .. } => ident,
-            _ => panic!("Expected fn bind"),
-        }
-    }
-
-    pub fn as_expr(&self) -> &expr::CoreExpr {
-        match self {
-            Self::Expr(expr) => expr,
-            _ => panic!("Expected Expr"),
-        }
-    }
+/// The class, style property or event named after `class:`, `style:` or `on:`, as in
+/// `class:active = $is_active` or `on:item-selected = @on_select`.
+struct Directive {
+    name: String,
+    span: Span,
 }
 
-impl syn::parse::Parse for Value {
+impl Directive {
+    /// Parses a name that may contain dashes, like `background-color`.
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        if input.peek(punc::FnBind) {
-            Ok(Value::FnBind {
-                bind_tok: input.parse()?,
-                ident: input.parse()?,
-            })
-        } else if input.peek(syn::token::Brace) {
-            let content;
-            Ok(Value::Stmt {
-                group: syn::braced!(content in input),
-                stmt: content.parse()?,
-            })
-        } else {
-            Ok(Value::Expr(input.parse()?))
+        let first = input.call(Ident::parse_any)?;
+        let mut name = first.to_string();
+
+        while input.peek(syn::Token![-]) {
+            input.parse::<syn::Token![-]>()?;
+            name.push('-');
+            name.push_str(&input.call(Ident::parse_any)?.to_string());
         }
+
+        Ok(Directive {
+            name,
+            span: first.span(),
+        })
     }
 }
 
-struct KeyValue {
-    key: Option<(Ident, syn::Token![:])>,
-    value: expr::CoreExpr,
+/// Parses `.name` modifiers, e.g. `.prevent.stop`.
+fn parse_modifiers(input: syn::parse::ParseStream) -> syn::Result<Vec<Ident>> {
+    let mut modifiers = Vec::new();
+    while input.peek(syn::Token![.]) {
+        input.parse::<syn::Token![.]>()?;
+        modifiers.push(input.call(Ident::parse_any)?);
+    }
+
+    Ok(modifiers)
 }
 
 impl syn::parse::Parse for KeyValue {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        if input.peek2(syn::Token![:]) {
-            Ok(KeyValue {
-                key: Some((input.parse()?, input.parse()?)),
-                value: input.parse()?,
-            })
-        } else {
-            Ok(KeyValue {
+        let fork = input.fork();
+        let key = (|| -> syn::Result<_> {
+            let key: Ident = fork.parse()?;
+            let modifiers = parse_modifiers(&fork)?;
+            let colon: syn::Token![:] = fork.parse()?;
+            Ok((key, modifiers, colon))
+        })();
+
+        let Ok((key, mut modifiers, colon)) = key else {
+            return Ok(KeyValue {
                 key: None,
+                directive: None,
+                modifiers: Vec::new(),
                 value: input.parse()?,
-            })
-        }
+            });
+        };
+        input.advance_to(&fork);
+
+        let directive = if key == "class" || key == "style" || key == "on" {
+            let fork = input.fork();
+            let directive = (|| -> syn::Result<_> {
+                let directive = Directive::parse(&fork)?;
+                let directive_modifiers = parse_modifiers(&fork)?;
+
+                if fork.peek(syn::Token![==]) {
+                    return Err(fork.error("expected `=`"));
+                }
+                fork.parse::<syn::Token![=]>()?;
+
+                Ok((directive, directive_modifiers))
+            })();
+
+            match directive {
+                Ok((directive, directive_modifiers)) => {
+                    input.advance_to(&fork);
+                    modifiers.extend(directive_modifiers);
+                    Some(directive)
+                }
+                Err(_) => None,
+            }
+        } else {
+            None
+        };
+
+        Ok(KeyValue {
+            key: Some((key, colon)),
+            directive,
+            modifiers,
+            value: input.parse()?,
+        })
     }
 }
 
 struct Arguments {
-    parens: syn::token::Paren,
     arguments: syn::punctuated::Punctuated<KeyValue, syn::Token![,]>,
 }
 
 impl syn::parse::Parse for Arguments {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let arguments;
+        syn::parenthesized!(arguments in input);
         Ok(Arguments {
-            parens: syn::parenthesized!(arguments in input),
             arguments: arguments.parse_terminated(KeyValue::parse)?,
         })
     }
@@ -143,7 +176,6 @@ enum Element {
     },
     Text(syn::LitStr),
     Include {
-        token: syn::Token![#],
         name: syn::Ident,
     },
 }
@@ -177,8 +209,8 @@ impl syn::parse::Parse for Element {
                 body: input.parse()?,
             })
         } else if input.peek(syn::Token![#]) {
+            input.parse::<syn::Token![#]>()?;
             Ok(Element::Include {
-                token: input.parse()?,
                 name: input.parse()?,
             })
         } else {
@@ -213,7 +245,6 @@ struct View {
     generics: Option<syn::Generics>,
     name: syn::Ident,
     generic_params: Option<syn::AngleBracketedGenericArguments>,
-    semi: syn::Token![;],
 
     elements: Vec<Element>,
 }
@@ -235,13 +266,13 @@ impl syn::parse::Parse for View {
         } else {
             None
         };
+        input.parse::<syn::Token![;]>()?;
 
         Ok(View {
             decl_token,
             generics,
             name,
             generic_params,
-            semi: input.parse()?,
 
             elements: {
                 let mut elements = Vec::new();
@@ -263,47 +294,170 @@ fn is_pascal(input: &str) -> bool {
     false
 }
 
-fn convert_expr_to_attr(expr: &syn::Expr) -> String {
+/// The text of an attribute written as words, e.g. `class: [container center]`, or `None` if
+/// the expression has to be evaluated.
+fn convert_expr_to_attr(expr: &syn::Expr) -> Option<String> {
     match expr {
         syn::Expr::Array(arr) => arr
             .elems
             .iter()
             .map(convert_expr_to_attr)
-            .collect::<Vec<_>>()
-            .join(" "),
-        syn::Expr::Lit(lit) => lit.to_token_stream().to_string(),
-        syn::Expr::Path(p) => p.to_token_stream().to_string(),
-        _ => "".to_string(),
+            .collect::<Option<Vec<_>>>()
+            .map(|words| words.join(" ")),
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(lit),
+            ..
+        }) => Some(lit.value()),
+        syn::Expr::Lit(lit) => Some(lit.to_token_stream().to_string()),
+        syn::Expr::Path(p) => Some(p.to_token_stream().to_string()),
+        _ => None,
+    }
+}
+
+/// A `tsz::debug::Origin` naming the view being generated and `element`, so the subscriptions
+/// made for it can be found in `tsz::debug::dump_graph`.
+fn origin(element: TokenStream) -> TokenStream {
+    quote! {
+        tsz::debug::Origin {
+            view: std::any::type_name::<Self>(),
+            element: #element,
+        }
     }
 }
 
-fn op_to_func_name(op: &BinOp) -> syn::Ident {
-    let name = match op {
-        BinOp::Eq(_) => "assign",
-        BinOp::AddEq(_) => "add",
-        BinOp::SubEq(_) => "sub",
-        BinOp::MulEq(_) => "mul",
-        BinOp::DivEq(_) => "div",
-        BinOp::RemEq(_) => "rem",
-        BinOp::ShrEq(_) => "shr",
-        BinOp::ShlEq(_) => "shl",
-        BinOp::BitAndEq(_) => "bitand",
-        BinOp::BitOrEq(_) => "bitor",
-        BinOp::BitXorEq(_) => "bitxor",
-        _ => panic!("Unsupported operator"),
+/// Applies `value` to `element` with `apply`, e.g. `tsz::set_attribute`, and applies it again
+/// whenever one of the states it reads changes. `label` names what is applied in the origin.
+fn get_reactive(
+    value: &expr::CoreExpr,
+    element: &syn::Ident,
+    label: &str,
+    apply: TokenStream,
+) -> syn::Result<TokenStream> {
+    let value = match value {
+        expr::CoreExpr::StateBind(bind) => {
+            let var_name = &bind.ident;
+            quote! { _self.#var_name.cloned() }
+        }
+        value => generate_expr(value, true)?,
     };
+    let origin = origin(quote!(concat!(stringify!(#element), ".", #label)));
 
-    syn::Ident::new(name, Span::call_site())
+    // The effect only holds on to the view weakly, like event listeners do.
+    Ok(quote! {
+        {
+            let _selfc = Rc::downgrade(&_self);
+            let #element = #element.clone();
+            tsz::debug::with_origin(#origin, || {
+                scope.effect(move || {
+                    let Some(_self) = _selfc.upgrade() else {
+                        return;
+                    };
+                    (#apply)(&#element, #value).expect("Updating element failed");
+                })
+            });
+        }
+    })
 }
 
-fn generate_expr(expr: &expr::CoreExpr, view_param: bool) -> TokenStream {
-    match expr {
+/// The `std::ops` method implementing a compound assignment operator, or `None` for `=`,
+/// and the span of the operator, so type errors point at it.
+fn op_to_method(op: &BinOp) -> (Option<TokenStream>, Span) {
+    let (names, span) = match op {
+        BinOp::Eq(token) => (None, token.spans[0]),
+        BinOp::AddEq(token) => (Some(("AddAssign", "add_assign")), token.spans[0]),
+        BinOp::SubEq(token) => (Some(("SubAssign", "sub_assign")), token.spans[0]),
+        BinOp::MulEq(token) => (Some(("MulAssign", "mul_assign")), token.spans[0]),
+        BinOp::DivEq(token) => (Some(("DivAssign", "div_assign")), token.spans[0]),
+        BinOp::RemEq(token) => (Some(("RemAssign", "rem_assign")), token.spans[0]),
+        BinOp::ShrEq(token) => (Some(("ShrAssign", "shr_assign")), token.spans[0]),
+        BinOp::ShlEq(token) => (Some(("ShlAssign", "shl_assign")), token.spans[0]),
+        BinOp::BitAndEq(token) => (Some(("BitAndAssign", "bitand_assign")), token.spans[0]),
+        BinOp::BitOrEq(token) => (Some(("BitOrAssign", "bitor_assign")), token.spans[0]),
+        BinOp::BitXorEq(token) => (Some(("BitXorAssign", "bitxor_assign")), token.spans[0]),
+    };
+
+    let method = names.map(|(tr, method)| {
+        let (tr, method) = (syn::Ident::new(tr, span), syn::Ident::new(method, span));
+        quote_spanned!(span=> std::ops::#tr::#method)
+    });
+
+    (method, span)
+}
+
+/// Replaces every `$state` in `tokens` with a clone of the state's current value.
+fn substitute_states(tokens: TokenStream, view_param: bool) -> TokenStream {
+    let view = if view_param { quote!(_self) } else { quote!(_selfc) };
+
+    let mut output = TokenStream::new();
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            proc_macro2::TokenTree::Punct(punct) if punct.as_char() == '$' => {
+                match tokens.peek() {
+                    Some(proc_macro2::TokenTree::Ident(ident)) => {
+                        let ident = ident.clone();
+                        tokens.next();
+                        output.extend(quote! { #view.#ident.cloned() });
+                    }
+                    _ => output.extend([proc_macro2::TokenTree::Punct(punct)]),
+                }
+            }
+            proc_macro2::TokenTree::Group(group) => {
+                let mut substituted = proc_macro2::Group::new(
+                    group.delimiter(),
+                    substitute_states(group.stream(), view_param),
+                );
+                substituted.set_span(group.span());
+                output.extend([proc_macro2::TokenTree::Group(substituted)]);
+            }
+            token => output.extend([token]),
+        }
+    }
+
+    output
+}
+
+/// Splits a call's arguments at the top-level commas.
+fn split_args(tokens: TokenStream) -> Vec<TokenStream> {
+    let mut args = vec![TokenStream::new()];
+    for token in tokens {
+        match token {
+            proc_macro2::TokenTree::Punct(punct) if punct.as_char() == ',' => {
+                args.push(TokenStream::new())
+            }
+            token => args.last_mut().unwrap().extend([token]),
+        }
+    }
+
+    args.retain(|arg| !arg.is_empty());
+    args
+}
+
+fn generate_expr(expr: &expr::CoreExpr, view_param: bool) -> syn::Result<TokenStream> {
+    let tokens = match expr {
+        // Plain Rust assignments such as `count = 1` are parsed whole, without reaching
+        // `CoreExpr::Assignment`.
+        expr::CoreExpr::Expr(
+            syn::Expr::Assign(syn::ExprAssign { left, .. })
+            | syn::Expr::AssignOp(syn::ExprAssignOp { left, .. }),
+        ) => {
+            return Err(not_a_state(syn::spanned::Spanned::span(left)));
+        }
         expr::CoreExpr::Expr(ex) => ex.to_token_stream(),
         expr::CoreExpr::FnBind(binding) => {
-            // dot = Some(syn::token::Dot { spans: binding.bind_token.spans });
-            let var_name = &binding.ident;
-
-            quote! {}
+            return Err(syn::Error::new(
+                binding.bind_token.spans[0],
+                format!(
+                    "`@{}` can only be used as an event handler, e.g. `click: @{0}`",
+                    binding.ident
+                ),
+            ));
+        }
+        expr::CoreExpr::Closure(closure) => {
+            return Err(syn::Error::new(
+                closure.brace.span,
+                "blocks can only be used as event handlers, e.g. `click: { $count += 1 }`",
+            ));
         }
         expr::CoreExpr::StateBind(binding) => {
             let dot = Some(syn::token::Dot {
@@ -312,87 +466,229 @@ fn generate_expr(expr: &expr::CoreExpr, view_param: bool) -> TokenStream {
             let var_name = &binding.ident;
 
             if view_param {
-                quote! { _self #dot #var_name.bind() }
+                quote! { tsz::ToBinding::to_binding(&_self #dot #var_name) }
+            } else {
+                // Read like `$state` inside other expressions, so any `Clone` state works.
+                quote_spanned! {var_name.span()=> _selfc #dot #var_name.cloned() }
+            }
+        }
+        expr::CoreExpr::StateMethod(call) => {
+            let var_name = &call.bind.ident;
+            let (dot, method) = (&call.dot_token, &call.method);
+            let args = split_args(call.args.clone())
+                .into_iter()
+                .map(|arg| substitute_states(arg, view_param))
+                .collect::<Vec<_>>();
+
+            if view_param {
+                quote! { _self.#var_name.with(|value| value #dot #method(#(#args),*)) }
             } else {
-                quote! { _selfc #dot #var_name.value() }
+                // The arguments are evaluated first, since they may read the state itself.
+                let arg_names = (0..args.len())
+                    .map(|index| syn::Ident::new(&format!("_arg{index}"), Span::call_site()))
+                    .collect::<Vec<_>>();
+
+                quote! {{
+                    #(let #arg_names = #args;)*
+                    _selfc.#var_name.value_mut().update(move |value| {
+                        value #dot #method(#(#arg_names),*);
+                    })
+                }}
             }
         }
+        expr::CoreExpr::StateExpr(expr) => substitute_states(expr.tokens.clone(), view_param),
         expr::CoreExpr::Assignment(expr::Assignment { left, op, right }) => {
-            match (left.as_ref(), right.as_ref()) {
-                (expr::CoreExpr::StateBind(bind), expr::CoreExpr::Expr(exp)) => {
-                    let op = op_to_func_name(op);
+            match left.as_ref() {
+                expr::CoreExpr::StateBind(bind) => {
                     let var_name = &bind.ident;
-
-                    quote! { _selfc.#var_name.value_mut().#op(#exp) }
+                    let value = generate_expr(right, view_param)?;
+
+                    // Skips unchanged values when the type allows it, see `tsz::Assignment`.
+                    // Only one of the traits is used, depending on the type.
+                    let (method, span) = op_to_method(op);
+                    let assignment = match method {
+                        None => quote_spanned! {span=>
+                            tsz::Assignment::new(_selfc.#var_name.value_mut(), #value)
+                        },
+                        Some(method) => quote_spanned! {span=>
+                            tsz::Operation::new(_selfc.#var_name.value_mut(), {
+                                let rhs = #value;
+                                move |value| #method(value, rhs)
+                            })
+                        },
+                    };
+
+                    quote_spanned! {span=> {
+                        #[allow(unused_imports)]
+                        use tsz::{AssignAny as _, AssignChanged as _};
+                        (&#assignment).__assign()
+                    }}
                 }
-                _ => panic!("Unexpected assignment"),
+                left => return Err(not_a_state(left.span())),
             }
         }
-        _ => panic!("Unexpected expression"),
-        // expr::CoreExpr::Closure(expr::Closure { expr, .. }) => {
-        //     get_event_from_stmt(expr, &name, &ident)
-        // }
-    }
+    };
+
+    Ok(tokens)
+}
+
+fn not_a_state(span: Span) -> syn::Error {
+    syn::Error::new(span, "only states can be assigned, e.g. `$count = 1`")
+}
+
+/// Listens to `event_name` on `element`, running `handler` with the view as `_selfc` and
+/// the event as `event`. The handler is spanned at `span`, so errors in it point at the view.
+fn listen(
+    element: &syn::Ident,
+    event_name: &syn::LitStr,
+    modifiers: &[Ident],
+    span: Span,
+    handler: TokenStream,
+) -> syn::Result<TokenStream> {
+    let event_type = event_type(&event_name.value());
+    let modifiers = Modifiers::parse(modifiers, event_type)?;
+    let event_type = syn::Ident::new(event_type, Span::call_site());
+
+    let options = modifiers.options();
+    let setup = modifiers.setup();
+    let guards = modifiers.guards();
+    let origin = origin(quote!(concat!(stringify!(#element), ".on:", #event_name)));
+    let handler = quote_spanned! {span=>
+        tsz::debug::with_origin(origin, || {
+            #handler
+        });
+    };
+
+    // The listener only holds on to the view weakly and is owned by the view's scope, so it
+    // doesn't keep the view alive.
+    Ok(quote! {
+        {
+            let _selfc = Rc::downgrade(&_self);
+            let origin = #origin;
+            #setup
+            #[allow(unused_variables)]
+            let handler = move |event: tsz::html::#event_type| {
+                #guards
+                let Some(_selfc) = _selfc.upgrade() else {
+                    return;
+                };
+                #handler
+            };
+            scope.listen_with(&#element, #event_name, #options, handler)?;
+        }
+    })
 }
 
 fn get_event_from_stmt(
     stmt: &expr::CoreExpr,
     event_name: &syn::LitStr,
     element: &syn::Ident,
-) -> TokenStream {
-    let expr = generate_expr(stmt, false);
-
-    quote! {
-        {
-            let _selfc = _self.clone();
-            let cb: Closure<dyn FnMut(Event)> = Closure::new(move |_| {
-                let _selfc = Rc::clone(&_selfc);
-                {
-                    #expr;
-                }
+    modifiers: &[Ident],
+) -> syn::Result<TokenStream> {
+    let expr = generate_expr(stmt, false)?;
+    let span = stmt.span();
+
+    // The handler can read the event as `event`.
+    listen(
+        element,
+        event_name,
+        modifiers,
+        span,
+        quote_spanned! {span=>
+            tsz::batch(|| {
+                #expr;
             });
-
-            #element.add_event_listener_with_callback(#event_name, &cb.as_ref().unchecked_ref())?;
-            cb.forget();
-        }
-    }
+        },
+    )
 }
 
 fn get_event(
     ident: &syn::Ident,
     event_name: &syn::LitStr,
     element: &syn::Ident,
+    modifiers: &[Ident],
+    view: &TokenStream,
     tok_span: Span,
-) -> TokenStream {
-    let path_string = ident.to_string();
-    let func_name = &path_string[..];
-    let func_ident = syn::Ident::new(func_name, Span::call_site());
-    // syn::LitChar::new(value, span);
-
-    let dot = syn::token::Dot { spans: [tok_span] };
+) -> syn::Result<TokenStream> {
+    let path_sep = syn::token::Colon2 {
+        spans: [tok_span, tok_span],
+    };
 
-    quote! {
-        {
-            let _selfc = _self.clone();
-            let cb: Closure<dyn FnMut(Event)> = Closure::new(move |_| {
-                let _selfc = Rc::clone(&_selfc);
-                _selfc #dot #func_ident()
-            });
+    // The method is called through `EventHandler`, so it may take the event or not. A method
+    // taking the wrong type of event is reported at its name.
+    let call = quote_spanned! {ident.span()=>
+        tsz::EventHandler::call(&#view #path_sep #ident, &*_selfc, event)
+    };
 
-            #element.add_event_listener_with_callback(#event_name, &cb.as_ref().unchecked_ref())?;
-            cb.forget();
-        }
-    }
+    listen(
+        element,
+        event_name,
+        modifiers,
+        ident.span(),
+        quote_spanned!(ident.span()=> tsz::batch(|| #call)),
+    )
 }
 
-// const
 lazy_static::lazy_static! {
-    static ref EVENTS: HashSet<&'static str> = HashSet::from_iter([
-        "click"
-    ]);
+    /// The standard DOM events, and the `web_sys` type of the event their handlers receive.
+    ///
+    /// Other events can be listened to with `on:name = ...`.
+    static ref EVENTS: HashMap<&'static str, &'static str> = {
+        let events: [(&str, &[&str]); 13] = [
+            ("MouseEvent", &[
+                "click", "dblclick", "auxclick", "contextmenu", "mousedown", "mouseup",
+                "mousemove", "mouseover", "mouseout", "mouseenter", "mouseleave",
+            ]),
+            ("KeyboardEvent", &["keydown", "keyup", "keypress"]),
+            ("InputEvent", &["input", "beforeinput"]),
+            ("FocusEvent", &["focus", "blur", "focusin", "focusout"]),
+            ("SubmitEvent", &["submit"]),
+            ("WheelEvent", &["wheel"]),
+            ("PointerEvent", &[
+                "pointerdown", "pointerup", "pointermove", "pointerover", "pointerout",
+                "pointerenter", "pointerleave", "pointercancel", "gotpointercapture",
+                "lostpointercapture",
+            ]),
+            ("TouchEvent", &["touchstart", "touchend", "touchmove", "touchcancel"]),
+            ("DragEvent", &[
+                "drag", "dragstart", "dragend", "dragenter", "dragleave", "dragover", "drop",
+            ]),
+            ("CompositionEvent", &["compositionstart", "compositionupdate", "compositionend"]),
+            ("AnimationEvent", &[
+                "animationstart", "animationend", "animationiteration", "animationcancel",
+            ]),
+            ("TransitionEvent", &[
+                "transitionstart", "transitionend", "transitionrun", "transitioncancel",
+            ]),
+            ("Event", &[
+                "change", "reset", "select", "invalid", "scroll", "scrollend", "load", "error",
+                "abort", "resize", "toggle", "copy", "cut", "paste", "play", "pause", "playing",
+                "ended", "canplay", "canplaythrough", "loadeddata", "loadedmetadata",
+                "timeupdate", "volumechange", "seeked", "seeking", "waiting",
+            ]),
+        ];
+
+        events
+            .into_iter()
+            .flat_map(|(ty, names)| names.iter().map(move |name| (*name, ty)))
+            .collect()
+    };
+}
+
+/// The type of the event that handlers of `name` receive.
+fn event_type(name: &str) -> &'static str {
+    EVENTS.get(name).copied().unwrap_or("Event")
 }
 
-fn walk_elements(index: &mut usize, parent: &Ident, element: &Element) -> TokenStream {
+/// `view` is the type of the view that `_self` refers to, which is the nested view inside
+/// the children of a nested view.
+fn walk_elements(
+    index: &mut usize,
+    parent: &Ident,
+    element: &Element,
+    view: &TokenStream,
+    errors: &mut Errors,
+) -> TokenStream {
     let mut tokens = TokenStream::new();
 
     match element {
@@ -413,7 +709,7 @@ fn walk_elements(index: &mut usize, parent: &Ident, element: &Element) -> TokenS
                 let args = if let Some(args) = arguments {
                     let mut params = Vec::new();
                     for arg in &args.arguments {
-                        params.push(generate_expr(&arg.value, true))
+                        params.push(errors.or_empty(generate_expr(&arg.value, true)))
                     }
                     params
                 } else {
@@ -429,15 +725,25 @@ fn walk_elements(index: &mut usize, parent: &Ident, element: &Element) -> TokenS
 
                             brace_token.surround(&mut closure_toks, |body_tokens| {
                                 for element in body {
-                                    let sub_tokens = walk_elements(index, &param_ident, element);
+                                    let sub_tokens = walk_elements(
+                                        index,
+                                        &param_ident,
+                                        element,
+                                        &quote!(#struct_name),
+                                        errors,
+                                    );
 
                                     body_tokens.extend(sub_tokens);
                                 }
                                 body_tokens.extend(quote!(Ok(())))
                             });
 
+                            // Children are created by the child view, which may do so after its
+                            // own `on_init` returned, so they enter the scope they are given.
                             quote! {
-                               Some(|_self, document, #param_ident| -> Result<(), JsValue> #closure_toks)
+                               Some(|_self, scope, document, #param_ident| -> Result<(), JsValue> {
+                                   scope.enter(|| #closure_toks)
+                               })
                             }
                         } else {
                             quote!(None)
@@ -446,9 +752,16 @@ fn walk_elements(index: &mut usize, parent: &Ident, element: &Element) -> TokenS
                     _ => quote!(None),
                 };
 
+                let scope_ident = syn::Ident::new(&format!("{}_scope", var_name), Span::call_site());
+
+                // The nested view gets a scope of its own, so contexts it provides from `new`
+                // or `on_init` are only visible to its subtree.
                 tokens.extend(quote! {
-                    #let_token #ident = Rc::new(#struct_name::new(#(#args),*));
-                    #ident.on_init(document.clone(), &#parent, #children)?;
+                    let #scope_ident = scope.child();
+                    #let_token #ident = #scope_ident.enter(|| Rc::new(#struct_name::new(#(#args),*)));
+                    #scope_ident.own_view(#ident.clone());
+                    #ident.on_init(&#scope_ident, document.clone(), &#parent, #children)?;
+                    scope.own_scope(#scope_ident);
                 });
 
                 return tokens;
@@ -458,48 +771,140 @@ fn walk_elements(index: &mut usize, parent: &Ident, element: &Element) -> TokenS
                     #let_token #ident = document.create_element(#tag)?;
                 });
 
+                // Toggles are applied after the static attributes, so a `class: [...]` list
+                // doesn't overwrite them.
+                let mut directives = TokenStream::new();
+
                 if let Some(args) = arguments {
                     for arg in &args.arguments {
-                        let name = arg
-                            .key
-                            .as_ref()
-                            .expect("Expected named argument for element")
-                            .0
-                            .to_string();
+                        let Some((key, _)) = &arg.key else {
+                            errors.push(syn::Error::new(
+                                arg.value.span(),
+                                format!(
+                                    "arguments of `{name}` must be named, e.g. `class: [container]` \
+                                     or `click: @on_click`"
+                                ),
+                            ));
+                            continue;
+                        };
+                        let name = key.to_string();
+
+                        let event = match &arg.directive {
+                            Some(directive) if name == "on" => {
+                                Some(syn::LitStr::new(&directive.name, directive.span))
+                            }
+                            Some(_) => None,
+                            None => EVENTS
+                                .contains_key(name.as_str())
+                                .then(|| syn::LitStr::new(&name, key.span())),
+                        };
+
+                        if let (Some(modifier), None) = (arg.modifiers.first(), &event) {
+                            errors.push(syn::Error::new(
+                                modifier.span(),
+                                format!("`{key}` is not an event, so it takes no modifiers"),
+                            ));
+                            continue;
+                        }
 
-                        if EVENTS.contains(name.as_str()) {
-                            let name = syn::LitStr::new(name.as_str(), Span::call_site());
+                        if let (Some(directive), None) = (&arg.directive, &event) {
+                            let target = syn::LitStr::new(&directive.name, directive.span);
+                            let apply = if name == "class" {
+                                quote!(|element, on| tsz::toggle_class(element, #target, on))
+                            } else {
+                                quote!(|element, value| tsz::set_style(element, #target, value))
+                            };
+
+                            let label = format!("{name}:{}", directive.name);
+                            let directive = get_reactive(&arg.value, &ident, &label, apply);
+                            directives.extend(errors.or_empty(directive));
+                            continue;
+                        }
 
+                        if let Some(name) = event {
                             match &arg.value {
                                 expr::CoreExpr::FnBind(expr::FnBind {
                                     bind_token,
                                     ident: bind_ident,
                                 }) => {
-                                    let event_value =
-                                        get_event(bind_ident, &name, &ident, bind_token.spans[0]);
-                                    tokens.extend(event_value);
+                                    let event_value = get_event(
+                                        bind_ident,
+                                        &name,
+                                        &ident,
+                                        &arg.modifiers,
+                                        view,
+                                        bind_token.spans[0],
+                                    );
+                                    tokens.extend(errors.or_empty(event_value));
                                 }
                                 expr::CoreExpr::Closure(expr::Closure { expr, .. }) => {
-                                    let event_value = get_event_from_stmt(expr, &name, &ident);
-                                    tokens.extend(event_value);
+                                    let event_value =
+                                        get_event_from_stmt(expr, &name, &ident, &arg.modifiers);
+                                    tokens.extend(errors.or_empty(event_value));
+                                }
+                                value => {
+                                    let event = name.value();
+                                    let usage = match arg.directive {
+                                        Some(_) => format!("on:{event} ="),
+                                        None => format!("{event}:"),
+                                    };
+                                    errors.push(syn::Error::new(
+                                        value.span(),
+                                        format!(
+                                            "expected an event handler for `{event}`, e.g. \
+                                             `{usage} @on_{method}` or `{usage} {{ $count += 1 }}`",
+                                            method = event.replace('-', "_"),
+                                        ),
+                                    ))
                                 }
-                                _ => panic!("Only Expected Function bind at the moment"),
                             }
                         } else {
-                            // let expr = arg.value.as_expr();
                             match &arg.value {
-                                expr::CoreExpr::Expr(ex) => {
-                                    let string = convert_expr_to_attr(ex);
-
-                                    tokens.extend(quote! {
+                                expr::CoreExpr::Expr(ex) => match convert_expr_to_attr(ex) {
+                                    Some(string) => tokens.extend(quote! {
                                         #ident.set_attribute(#name, #string)?;
-                                    });
+                                    }),
+                                    None => tokens.extend(quote! {
+                                        tsz::set_attribute(&#ident, #name, #ex)?;
+                                    }),
+                                },
+                                value @ (expr::CoreExpr::StateBind(_)
+                                | expr::CoreExpr::StateMethod(_)
+                                | expr::CoreExpr::StateExpr(_)) => {
+                                    let attribute = get_reactive(
+                                        value,
+                                        &ident,
+                                        &name,
+                                        quote!(|element, value| tsz::set_attribute(element, #name, value)),
+                                    );
+                                    tokens.extend(errors.or_empty(attribute));
+                                }
+                                // A handler for something that isn't an event is most likely a
+                                // misspelled event.
+                                expr::CoreExpr::FnBind(_) | expr::CoreExpr::Closure(_) => {
+                                    errors.push(errors::suggest(
+                                        key.span(),
+                                        &format!(
+                                            "`{key}` is not a known event, custom events are \
+                                             listened to with `on:{key} = ...`"
+                                        ),
+                                        &name,
+                                        EVENTS.keys().copied(),
+                                    ))
                                 }
-                                _ => panic!("Unexpected expression for attribute"),
+                                value => errors.push(syn::Error::new(
+                                    value.span(),
+                                    format!(
+                                        "expected a value for the `{key}` attribute, e.g. \
+                                         `{key}: [container]` or `{key}: \"value\"`"
+                                    ),
+                                )),
                             }
                         }
                     }
                 }
+
+                tokens.extend(directives);
             };
 
             match &body {
@@ -507,7 +912,7 @@ fn walk_elements(index: &mut usize, parent: &Ident, element: &Element) -> TokenS
                     if !body.is_empty() {
                         brace_token.surround(&mut tokens, |body_tokens| {
                             for element in body {
-                                let sub_tokens = walk_elements(index, &ident, element);
+                                let sub_tokens = walk_elements(index, &ident, element, view, errors);
 
                                 body_tokens.extend(sub_tokens);
                             }
@@ -523,7 +928,6 @@ fn walk_elements(index: &mut usize, parent: &Ident, element: &Element) -> TokenS
         }
         Element::Text(lit_str) => {
             let string = lit_str.value();
-            let mut count = 0;
             let mut buf = String::new();
             let mut var_buf = Vec::new();
 
@@ -531,12 +935,10 @@ fn walk_elements(index: &mut usize, parent: &Ident, element: &Element) -> TokenS
                 let mut text_and_pattern = chunk.split_inclusive('{');
                 let text = text_and_pattern.next().unwrap();
                 buf.push_str(text);
-                count += text.len();
 
                 if let Some(p) = text_and_pattern.next() {
                     if let Some(' ' | '}') = p.chars().next() {
                         buf.push_str(p);
-                        count += p.len();
                         continue;
                     }
 
@@ -555,17 +957,30 @@ fn walk_elements(index: &mut usize, parent: &Ident, element: &Element) -> TokenS
                     } else {
                         let mut var_and_curly = var.split_terminator('}');
 
-                        let var = var_and_curly.next().unwrap();
+                        let var = var_and_curly.next().unwrap_or_default();
                         // let var = syn::Ident::new(var, proc_macro2::Span::call_site());
 
                         var_buf.push(var);
 
                         buf.push('}');
 
-                        assert!(var_and_curly.next().is_none());
+                        if var_and_curly.next().is_some() {
+                            errors.push(syn::Error::new(
+                                lit_str.span(),
+                                format!("unexpected `}}` after `{{{var}`"),
+                            ));
+                        }
                     }
 
-                    assert!(var_and_format.next().is_none());
+                    if var_and_format.next().is_some() {
+                        errors.push(syn::Error::new(
+                            lit_str.span(),
+                            format!(
+                                "`{{{}` has more than one format spec",
+                                p.trim_end_matches('}')
+                            ),
+                        ));
+                    }
                 }
             }
 
@@ -582,15 +997,31 @@ fn walk_elements(index: &mut usize, parent: &Ident, element: &Element) -> TokenS
             let mut subscribers = Vec::new();
             let mut vars = Vec::new();
             let mut re_fmt_vars = Vec::new();
+            let mut states = Vec::new();
 
             for value in &var_buf {
-                if value.starts_with('$') {
-                    let var_name = syn::Ident::new(&value[1..], Span::call_site());
-                    vars.push(quote! {_self.#var_name.value()});
+                let (state, name) = match value.strip_prefix('$') {
+                    Some(name) => (true, name),
+                    None => (false, *value),
+                };
+
+                let Ok(mut var_name) = syn::parse_str::<Ident>(name) else {
+                    errors.push(syn::Error::new(
+                        lit_str.span(),
+                        format!(
+                            "`{{{value}}}` must name a state of the view, e.g. `{{$count}}`, or a \
+                             variable in scope"
+                        ),
+                    ));
+                    continue;
+                };
+                var_name.set_span(lit_str.span());
 
-                    re_fmt_vars.push(quote! {_selfc.#var_name.value()});
+                if state {
+                    vars.push(quote! {_self.#var_name.borrow()});
+                    re_fmt_vars.push(quote! {_selfc.#var_name.borrow()});
+                    states.push(var_name);
                 } else {
-                    let var_name = syn::Ident::new(value, Span::call_site());
                     vars.push(quote! {#var_name});
                     re_fmt_vars.push(quote! {#var_name});
                 }
@@ -599,22 +1030,25 @@ fn walk_elements(index: &mut usize, parent: &Ident, element: &Element) -> TokenS
             let format = quote! { let content = format!(#string, #(#vars),*); };
             let re_format = quote! { let content = format!(#string, #(#re_fmt_vars),*); };
 
-            for value in &var_buf {
-                if value.starts_with('$') {
-                    let var_name = syn::Ident::new(&value[1..], Span::call_site());
-
-                    let new_name =
-                        syn::Ident::new(&format!("{}_clone", bind.to_string()), Span::call_site());
-
-                    subscribers.push(quote! {
-                        let _selfc = _self.clone();
-                        let #new_name = #bind.clone();
-                        _self.#var_name.subscribe(move |value| {
-                            #re_format
-                            #new_name.set_text_content(Some(&content));
-                        });
-                    });
-                }
+            let origin = origin(quote!(stringify!(#bind)));
+            for var_name in &states {
+                let new_name =
+                    syn::Ident::new(&format!("{}_clone", bind.to_string()), Span::call_site());
+
+                // The subscriber is tagged with the view that declared the text node, so
+                // it can be found in `tsz::debug::dump_graph`.
+                subscribers.push(quote! {
+                    let _selfc = Rc::downgrade(&_self);
+                    let #new_name = #bind.clone();
+                    let origin = #origin;
+                    scope.own(tsz::debug::with_origin(origin, || _self.#var_name.subscribe(move |value| {
+                        let Some(_selfc) = _selfc.upgrade() else {
+                            return;
+                        };
+                        #re_format
+                        #new_name.set_text_content(Some(&content));
+                    })));
+                });
             }
 
             tokens.extend(quote! {
@@ -627,7 +1061,7 @@ fn walk_elements(index: &mut usize, parent: &Ident, element: &Element) -> TokenS
         Element::Include { name, .. } => {
             tokens.extend(quote! {
                 if let Some(view) = &#name {
-                    view (&_self, &document, &#parent)?;
+                    view (&_self, scope, &document, &#parent)?;
                 }
             })
         }
@@ -653,18 +1087,24 @@ pub fn view(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 
     let mut index = 0;
     let mut tokens = Vec::new();
-    let element_len = elements.len();
+    let mut errors = Errors::default();
 
     for element in &elements {
         let sub_tokens = walk_elements(
             &mut index,
             &Ident::new_raw("__body", proc_macro2::Span::call_site()),
             element,
+            &quote!(Self),
+            &mut errors,
         );
 
         tokens.push(sub_tokens);
     }
 
+    if let Err(errors) = errors.finish() {
+        return errors.into_compile_error().into();
+    }
+
     let impl_tok = syn::token::Impl {
         span: decl_token.span,
     };
@@ -677,17 +1117,65 @@ pub fn view(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         use web_sys::Event;
 
         #impl_tok #generics #name #generic_params {
-            pub fn on_init(self: Rc<Self>, document: Rc<tsz::html::Document>, parent: &tsz::html::Element, children: Option<fn (&Rc<Self>, &Rc<tsz::html::Document>, &tsz::html::Element) -> Result<(), JsValue>>) -> Result<[tsz::html::Element; #element_len], JsValue> {
+            pub fn on_init(self: Rc<Self>, scope: &tsz::Scope, document: Rc<tsz::html::Document>, parent: &tsz::html::Element, children: Option<fn (&Rc<Self>, &tsz::Scope, &Rc<tsz::html::Document>, &tsz::html::Element) -> Result<(), JsValue>>) -> Result<(), JsValue> {
                 // let Self { value } = self;
                 let __body = document.body().expect("Unable to get document body");
                 let _self = self;
 
-                #(#tokens);*
+                scope.enter(|| {
+                    #(#tokens);*
 
-                Ok([])
+                    Ok(())
+                })
             }
         }
     };
 
     output.into()
 }
+
+#[proc_macro_derive(Snapshot, attributes(snapshot))]
+pub fn derive_snapshot(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+
+    snapshot::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_map_to_their_web_sys_type() {
+        assert_eq!(event_type("click"), "MouseEvent");
+        assert_eq!(event_type("keydown"), "KeyboardEvent");
+        assert_eq!(event_type("input"), "InputEvent");
+        assert_eq!(event_type("submit"), "SubmitEvent");
+        assert_eq!(event_type("pointermove"), "PointerEvent");
+        assert_eq!(event_type("drop"), "DragEvent");
+        assert_eq!(event_type("transitionend"), "TransitionEvent");
+        assert_eq!(event_type("change"), "Event");
+    }
+
+    #[test]
+    fn custom_events_receive_a_plain_event() {
+        assert!(!EVENTS.contains_key("my-event"));
+        assert_eq!(event_type("my-event"), "Event");
+    }
+
+    #[test]
+    fn every_event_type_is_enabled_in_web_sys() {
+        // The handlers receive `tsz::html::*`, re-exported from `web_sys`, which only has the
+        // types whose feature `tsz` enables.
+        let manifest = include_str!("../../tsz/Cargo.toml");
+
+        for ty in EVENTS.values() {
+            assert!(
+                manifest.contains(&format!("'{ty}'")),
+                "the `{ty}` feature of `web-sys` isn't enabled"
+            );
+        }
+    }
+}
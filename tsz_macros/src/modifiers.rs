@@ -0,0 +1,312 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Ident;
+
+use crate::errors;
+
+/// The modifiers that change how an event is listened to.
+const MODIFIERS: [&str; 5] = ["prevent", "stop", "once", "capture", "passive"];
+
+/// Key filters, and the `KeyboardEvent::key` they match.
+const KEYS: [(&str, &str); 14] = [
+    ("enter", "Enter"),
+    ("escape", "Escape"),
+    ("esc", "Escape"),
+    ("space", " "),
+    ("tab", "Tab"),
+    ("backspace", "Backspace"),
+    ("delete", "Delete"),
+    ("up", "ArrowUp"),
+    ("down", "ArrowDown"),
+    ("left", "ArrowLeft"),
+    ("right", "ArrowRight"),
+    ("home", "Home"),
+    ("end", "End"),
+    ("insert", "Insert"),
+];
+
+/// Filters on the modifier keys held down, and the `KeyboardEvent` method that reads them.
+const SYSTEM_KEYS: [(&str, &str); 4] = [
+    ("ctrl", "ctrl_key"),
+    ("shift", "shift_key"),
+    ("alt", "alt_key"),
+    ("meta", "meta_key"),
+];
+
+/// The modifiers after an event name, as in `submit.prevent: @save` or
+/// `keydown.ctrl.enter: @send`.
+#[derive(Default)]
+pub(crate) struct Modifiers {
+    prevent: bool,
+    stop: bool,
+    once: bool,
+    capture: bool,
+    passive: bool,
+    key: Option<&'static str>,
+    system_keys: Vec<&'static str>,
+}
+
+impl Modifiers {
+    /// Reads `modifiers` for a listener that receives a `event_type`.
+    pub(crate) fn parse(modifiers: &[Ident], event_type: &str) -> syn::Result<Modifiers> {
+        let mut parsed = Modifiers::default();
+        let is_keyboard = event_type == "KeyboardEvent";
+
+        for modifier in modifiers {
+            let name = modifier.to_string();
+
+            let flag = match name.as_str() {
+                "prevent" => Some(&mut parsed.prevent),
+                "stop" => Some(&mut parsed.stop),
+                "once" => Some(&mut parsed.once),
+                "capture" => Some(&mut parsed.capture),
+                "passive" => Some(&mut parsed.passive),
+                _ => None,
+            };
+            if let Some(flag) = flag {
+                if *flag {
+                    return Err(syn::Error::new(
+                        modifier.span(),
+                        format!("`{name}` is given more than once"),
+                    ));
+                }
+                *flag = true;
+                continue;
+            }
+
+            let key = KEYS.iter().find(|(filter, _)| *filter == name);
+            let system_key = SYSTEM_KEYS.iter().find(|(filter, _)| *filter == name);
+            if key.is_none() && system_key.is_none() {
+                let candidates = MODIFIERS
+                    .iter()
+                    .chain(KEYS.iter().map(|(filter, _)| filter))
+                    .chain(SYSTEM_KEYS.iter().map(|(filter, _)| filter))
+                    .copied();
+
+                return Err(errors::suggest(
+                    modifier.span(),
+                    &format!("`{name}` is not a known event modifier or key"),
+                    &name,
+                    candidates,
+                ));
+            }
+
+            if !is_keyboard {
+                return Err(syn::Error::new(
+                    modifier.span(),
+                    format!(
+                        "`{name}` filters keys, which only keyboard events like `keydown` have"
+                    ),
+                ));
+            }
+
+            if let Some((_, key)) = key {
+                if parsed.key.is_some() {
+                    return Err(syn::Error::new(
+                        modifier.span(),
+                        "an event can only be filtered on one key",
+                    ));
+                }
+                parsed.key = Some(key);
+            }
+            if let Some((_, method)) = system_key {
+                parsed.system_keys.push(method);
+            }
+        }
+
+        if parsed.passive && parsed.prevent {
+            let passive = modifiers.iter().find(|modifier| *modifier == "passive");
+            return Err(syn::Error::new(
+                passive.unwrap().span(),
+                "a `passive` listener can't `prevent` the default action",
+            ));
+        }
+
+        Ok(parsed)
+    }
+
+    fn filters_keys(&self) -> bool {
+        self.key.is_some() || !self.system_keys.is_empty()
+    }
+
+    /// The `tsz::ListenerOptions` to add the listener with.
+    pub(crate) fn options(&self) -> TokenStream {
+        let Modifiers {
+            capture, passive, ..
+        } = self;
+        // The browser would remove a `once` listener after an event that was filtered out,
+        // so filtered listeners only run once by themselves.
+        let once = self.once && !self.filters_keys();
+
+        quote! {
+            tsz::ListenerOptions {
+                capture: #capture,
+                once: #once,
+                passive: #passive,
+            }
+        }
+    }
+
+    /// Declares what the guards need, before the listener.
+    pub(crate) fn setup(&self) -> TokenStream {
+        if self.once && self.filters_keys() {
+            quote!(let _fired = std::cell::Cell::new(false);)
+        } else {
+            TokenStream::new()
+        }
+    }
+
+    /// Returns early from the listener for events filtered out by a key or after a filtered
+    /// `once` listener ran, then stops the event or prevents its default action, before the
+    /// handler runs.
+    pub(crate) fn guards(&self) -> TokenStream {
+        let mut tokens = TokenStream::new();
+
+        if let Some(key) = self.key {
+            tokens.extend(quote! {
+                if event.key() != #key {
+                    return;
+                }
+            });
+        }
+        for method in &self.system_keys {
+            let method = Ident::new(method, proc_macro2::Span::call_site());
+            tokens.extend(quote! {
+                if !event.#method() {
+                    return;
+                }
+            });
+        }
+
+        if self.once && self.filters_keys() {
+            tokens.extend(quote! {
+                if _fired.replace(true) {
+                    return;
+                }
+            });
+        }
+
+        if self.prevent {
+            tokens.extend(quote!(event.prevent_default();));
+        }
+        if self.stop {
+            tokens.extend(quote!(event.stop_propagation();));
+        }
+
+        tokens
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses dot-separated `modifiers`, e.g. `"ctrl.enter"`.
+    fn parse(modifiers: &str, event_type: &str) -> syn::Result<Modifiers> {
+        let modifiers = modifiers
+            .split('.')
+            .map(|name| Ident::new(name, proc_macro2::Span::call_site()))
+            .collect::<Vec<_>>();
+
+        Modifiers::parse(&modifiers, event_type)
+    }
+
+    fn parsed(modifiers: &str, event_type: &str) -> Modifiers {
+        match parse(modifiers, event_type) {
+            Ok(parsed) => parsed,
+            Err(error) => panic!("expected `{modifiers}` to be accepted: {error}"),
+        }
+    }
+
+    fn error(modifiers: &str, event_type: &str) -> String {
+        match parse(modifiers, event_type) {
+            Ok(_) => panic!("expected `{modifiers}` to be rejected"),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    #[test]
+    fn rejects_duplicate_modifiers() {
+        assert_eq!(
+            error("prevent.stop.prevent", "MouseEvent"),
+            "`prevent` is given more than once"
+        );
+    }
+
+    #[test]
+    fn rejects_passive_listeners_that_prevent() {
+        assert_eq!(
+            error("prevent.passive", "WheelEvent"),
+            "a `passive` listener can't `prevent` the default action"
+        );
+    }
+
+    #[test]
+    fn key_filters_need_keyboard_events() {
+        assert_eq!(
+            error("enter", "MouseEvent"),
+            "`enter` filters keys, which only keyboard events like `keydown` have"
+        );
+        assert_eq!(
+            error("ctrl", "Event"),
+            "`ctrl` filters keys, which only keyboard events like `keydown` have"
+        );
+        parsed("ctrl.enter", "KeyboardEvent");
+    }
+
+    #[test]
+    fn rejects_filtering_on_two_keys() {
+        assert_eq!(
+            error("enter.escape", "KeyboardEvent"),
+            "an event can only be filtered on one key"
+        );
+    }
+
+    #[test]
+    fn suggests_known_modifiers() {
+        assert_eq!(
+            error("prevnt", "Event"),
+            "`prevnt` is not a known event modifier or key\nhelp: did you mean `prevent`?"
+        );
+    }
+
+    #[test]
+    fn filtered_once_listeners_guard_themselves() {
+        let modifiers = parsed("once.enter", "KeyboardEvent");
+
+        // The browser must not remove the listener after an event that was filtered out.
+        let options = quote! {
+            tsz::ListenerOptions { capture: false, once: false, passive: false, }
+        };
+        assert_eq!(modifiers.options().to_string(), options.to_string());
+
+        assert_eq!(
+            modifiers.setup().to_string(),
+            quote!(let _fired = std::cell::Cell::new(false);).to_string()
+        );
+        let guards = quote! {
+            if event.key() != "Enter" {
+                return;
+            }
+            if _fired.replace(true) {
+                return;
+            }
+        };
+        assert_eq!(modifiers.guards().to_string(), guards.to_string());
+    }
+
+    #[test]
+    fn unfiltered_once_listeners_use_the_browser_option() {
+        let modifiers = parsed("once.stop", "MouseEvent");
+
+        let options = quote! {
+            tsz::ListenerOptions { capture: false, once: true, passive: false, }
+        };
+        assert_eq!(modifiers.options().to_string(), options.to_string());
+        assert!(modifiers.setup().is_empty());
+        assert_eq!(
+            modifiers.guards().to_string(),
+            quote!(event.stop_propagation();).to_string()
+        );
+    }
+}
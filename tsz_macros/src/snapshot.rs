@@ -0,0 +1,88 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// Generates `tsz::Snapshot` for a struct by snapshotting each of its fields.
+pub fn derive(input: syn::DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "`Snapshot` can only be derived for structs with named fields",
+            ))
+        }
+    };
+
+    let mut names = Vec::new();
+    let mut keys = Vec::new();
+    for field in fields {
+        if is_skipped(field)? {
+            continue;
+        }
+
+        let name = field.ident.as_ref().expect("Named field without a name");
+        keys.push(name.to_string());
+        names.push(name);
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics tsz::Snapshot for #ident #ty_generics #where_clause {
+            fn snapshot(&self) -> Result<tsz::__serde_json::Value, tsz::__serde_json::Error> {
+                let mut fields = tsz::__serde_json::Map::new();
+                #(
+                    fields.insert(#keys.to_string(), tsz::Snapshot::snapshot(&self.#names)?);
+                )*
+                Ok(tsz::__serde_json::Value::Object(fields))
+            }
+
+            fn prepare_restore(
+                &self,
+                snapshot: tsz::__serde_json::Value,
+            ) -> Result<tsz::Restore<'_>, tsz::__serde_json::Error> {
+                let tsz::__serde_json::Value::Object(mut fields) = snapshot else {
+                    return Err(<tsz::__serde_json::Error as tsz::__serde::de::Error>::custom(
+                        concat!("expected an object to restore `", stringify!(#ident), "`"),
+                    ));
+                };
+
+                // Every field is deserialized before any is assigned, and fields missing from
+                // the snapshot keep their current value.
+                let restores: Vec<Option<tsz::Restore<'_>>> = vec![#(
+                    match fields.remove(#keys) {
+                        Some(value) => Some(tsz::Snapshot::prepare_restore(&self.#names, value)?),
+                        None => None,
+                    },
+                )*];
+
+                Ok(Box::new(move || {
+                    for restore in restores.into_iter().flatten() {
+                        restore();
+                    }
+                }))
+            }
+        }
+    })
+}
+
+fn is_skipped(field: &syn::Field) -> syn::Result<bool> {
+    let mut skipped = false;
+    for attr in &field.attrs {
+        if !attr.path.is_ident("snapshot") {
+            continue;
+        }
+
+        let option: syn::Ident = attr.parse_args()?;
+        if option != "skip" {
+            return Err(syn::Error::new_spanned(option, "expected `skip`"));
+        }
+        skipped = true;
+    }
+
+    Ok(skipped)
+}